@@ -324,126 +324,117 @@ pub fn expand(input: TokenStream) -> TokenStream {
     }
 }
 
-#[proc_macro_derive(CssPseudoMacro)]
+fn is_string_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => {
+            type_path.qself.is_none() && type_path.path.is_ident("String")
+        }
+        _ => false,
+    }
+}
+
+// Generates a `PseudoTrait` builder method for every `Pseudo` variant. Unit variants take no
+// argument, `#[selector]` variants take anything implementing `PseudoSelector` and other
+// variants take their field. `#[pseudo_fn = "name"]` renames the method, e.g. for keywords.
+#[proc_macro_derive(CssPseudoMacro, attributes(selector, pseudo_fn))]
 pub fn expand_pseudo(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     if let syn::Data::Enum(data_enum) = input.data {
-        let func_impls_defn = data_enum
-            .variants
-            .iter()
-            .filter(|v| v.fields == syn::Fields::Unit)
-            .map(|v| {
-                let snake_case_variant =
-                    format_ident!("{}", v.ident.clone().to_string().to_snake_case());
-                quote! {
-                    fn #snake_case_variant(self) -> Style;
+        let mut func_defns = vec![];
+        let mut func_impls = vec![];
+
+        for v in data_enum.variants.iter() {
+            let big_name = v.ident.clone();
+            let mut fn_name = v.ident.to_string().to_snake_case();
+            let mut is_selector = false;
+
+            for attr in v.attrs.iter() {
+                match attr.parse_meta() {
+                    Ok(Meta::Path(ref path)) if path.is_ident("selector") => is_selector = true,
+                    Ok(Meta::NameValue(MetaNameValue {
+                        ref path, ref lit, ..
+                    })) if path.is_ident("pseudo_fn") => {
+                        if let Lit::Str(lit) = lit {
+                            fn_name = lit.value();
+                        }
+                    }
+                    _ => {}
                 }
-            });
-
-        let trait_definition = quote! {
-            pub trait PseudoTrait {
-                #(
-                #func_impls_defn
-                )*
-
-                fn pseudo( self, val: &str)  -> Style ;
-
-                fn lang( self, val: &str)  -> Style ;
-
-                fn not( self, val: &str)  -> Style ;
-
-                fn nth_child( self, val: usize)  -> Style ;
-
-
-                fn nth_last_child( self, val: usize)  -> Style ;
-
-                fn nth_last_of_type( self, val: usize)  -> Style ;
-
-
-                fn nth_of_type( self, val: usize)  -> Style ;
             }
-        };
+            let fn_name = format_ident!("{}", fn_name);
 
-        let func_impls = data_enum
-            .variants
-            .iter()
-            .filter(|v| v.fields == syn::Fields::Unit)
-            .map(|v| {
-                let snake_case_variant =
-                    format_ident!("{}", v.ident.clone().to_string().to_snake_case());
-                let big_name = v.ident.clone();
-                quote! {
+            match &v.fields {
+                syn::Fields::Unit => {
+                    func_defns.push(quote! {
+                        fn #fn_name(self) -> Style;
+                    });
+                    func_impls.push(quote! {
                         #[track_caller]
-                        fn #snake_case_variant(mut self) -> Style {
-
+                        fn #fn_name(mut self) -> Style {
                             self.updated_at.push(format!("{}", Location::caller()));
-                            self.pseudo = Pseudo::#big_name ;
-
+                            self.add_pseudo(Pseudo::#big_name);
                             self
                         }
-
-
-                }
-            });
-
-        let trait_impl = quote! {
-
-            impl PseudoTrait for Style {
-                #(#func_impls)*
-   
-                fn pseudo(mut self, val: &str)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::Custom(val.to_string());
-
-                    self
-                }
-                
-                fn lang(mut self, val: &str)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::Lang(val.to_string());
-
-                    self
-                }
-                fn not(mut self, val: &str)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::Not(val.to_string());
-
-                    self
-                }
-                fn nth_child(mut self, val: usize)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::NthChild(val);
-
-                    self
-                }
-
-                fn nth_last_child(mut self, val: usize)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::NthLastChild(val);
-
-                    self
+                    });
                 }
-
-                fn nth_last_of_type(mut self, val: usize)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::NthLastOfType(val);
-
-                    self
+                syn::Fields::Unnamed(fields) if is_selector => {
+                    if fields.unnamed.len() != 1 {
+                        panic!("#[selector] pseudos need exactly one field");
+                    }
+                    func_defns.push(quote! {
+                        fn #fn_name<S: PseudoSelector>(self, val: S) -> Style;
+                    });
+                    func_impls.push(quote! {
+                        #[track_caller]
+                        fn #fn_name<S: PseudoSelector>(mut self, val: S) -> Style {
+                            self.updated_at.push(format!("{}", Location::caller()));
+                            self.add_pseudo(Pseudo::#big_name(val.pseudo_selector()));
+                            self
+                        }
+                    });
                 }
-
-                fn nth_of_type(mut self, val: usize)  -> Style {
-                    self.updated_at.push(format!("{}", Location::caller()));
-                    self.pseudo = Pseudo::NthOfType(val);
-
-                    self
+                syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let field_ty = &fields.unnamed.first().unwrap().ty;
+                    // String fields are taken as &str, anything else through Into
+                    if is_string_type(field_ty) {
+                        func_defns.push(quote! {
+                            fn #fn_name(self, val: &str) -> Style;
+                        });
+                        func_impls.push(quote! {
+                            #[track_caller]
+                            fn #fn_name(mut self, val: &str) -> Style {
+                                self.updated_at.push(format!("{}", Location::caller()));
+                                self.add_pseudo(Pseudo::#big_name(val.to_string()));
+                                self
+                            }
+                        });
+                    } else {
+                        func_defns.push(quote! {
+                            fn #fn_name<V: Into<#field_ty>>(self, val: V) -> Style;
+                        });
+                        func_impls.push(quote! {
+                            #[track_caller]
+                            fn #fn_name<V: Into<#field_ty>>(mut self, val: V) -> Style {
+                                self.updated_at.push(format!("{}", Location::caller()));
+                                self.add_pseudo(Pseudo::#big_name(val.into()));
+                                self
+                            }
+                        });
+                    }
                 }
+                _ => panic!("Pseudo variants can have at most one unnamed field"),
             }
-        };
+        }
 
         let exp = quote! {
-            #trait_definition
-            #trait_impl
+            pub trait PseudoTrait {
+                #(#func_defns)*
+            }
+
+            impl PseudoTrait for Style {
+                #(#func_impls)*
+            }
         };
 
         exp.into()
@@ -505,6 +496,513 @@ pub fn generate_from_strs(input: TokenStream) -> TokenStream {
     TokenStream::from(exp)
 }
 
+// Proc macro to render a fully literal style chain at compile time.
+//
+// static_style!(s().name("card").padding(px(8)).color("#333").hover())
+//
+// expands to `seed_style_preview::StaticStyle("seedstatic-card-<hash>")` and adds
+// `.seedstatic-card-<hash>:hover{ padding: 8px; color: #333; }` to
+// `$OUT_DIR/seed_style_static.css`, so the css can be shipped as a cacheable asset.
+// The hash is computed from the media query, pseudo and declarations, so the class name is
+// stable between builds and two different styles never share a class.
+//
+// Only property methods named after their css property are supported (`padding` not `p`),
+// with string, number or `px`/`rem`/`em`/`vw`/`vh`/`cm`/`pc` literal arguments,
+// plus `name`, `media` and argument-less pseudo methods.
+// OUT_DIR is only set for crates with a build script, an empty `build.rs` is enough.
+//
+// Every class is written to its own file in `$OUT_DIR/seed_style_static/`, then
+// `seed_style_static.css` is rebuilt from all files of that directory. Incremental builds that
+// only re-expand some invocations therefore keep the rules of the others. Rules of removed
+// invocations stay until the next clean build. Files are written through a temporary file and
+// a rename so a concurrent build never reads a half written one. When several compilations
+// expand static styles at the same time, concatenate the directory in a build step instead
+// of relying on the combined file.
+#[proc_macro]
+pub fn static_style(input: TokenStream) -> TokenStream {
+    let chain = parse_macro_input!(input as Expr);
+    match expand_static_style(&chain).and_then(|(class_name, css)| {
+        write_static_css(&chain, &class_name, css)?;
+        Ok(class_name)
+    }) {
+        Ok(class_name) => {
+            let seed_style = seed_style_path();
+            quote!(#seed_style::StaticStyle(#class_name)).into()
+        }
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn write_static_css(chain: &Expr, class_name: &str, css: String) -> syn::Result<()> {
+    let out_dir = std::env::var("OUT_DIR").map_err(|_| {
+        syn::Error::new_spanned(
+            chain,
+            "static_style! writes its css to $OUT_DIR, which is only set for crates with a build script, add an empty `build.rs`",
+        )
+    })?;
+    let out_dir = std::path::Path::new(&out_dir);
+    let class_dir = out_dir.join("seed_style_static");
+
+    std::fs::create_dir_all(&class_dir)
+        .and_then(|_| write_file_atomically(&class_dir.join(format!("{}.css", class_name)), &css))
+        .and_then(|_| concatenate_css_files(&class_dir))
+        .and_then(|contents| {
+            write_file_atomically(&out_dir.join("seed_style_static.css"), &contents)
+        })
+        .map_err(|err| {
+            syn::Error::new_spanned(
+                chain,
+                format!(
+                    "static_style! could not write seed_style_static.css: {}",
+                    err
+                ),
+            )
+        })
+}
+
+fn write_file_atomically(path: &std::path::Path, contents: &str) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+    std::fs::write(&tmp_path, contents).and_then(|_| std::fs::rename(&tmp_path, path))
+}
+
+// The `.css` files of `dir` sorted by name, so the output does not depend on expansion order.
+fn concatenate_css_files(dir: &std::path::Path) -> std::io::Result<String> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.retain(|path| path.extension().map_or(false, |extension| extension == "css"));
+    paths.sort();
+
+    let mut css = String::new();
+    for path in paths {
+        css.push_str(&std::fs::read_to_string(path)?);
+    }
+    Ok(css)
+}
+
+// The generated class name and its css.
+fn expand_static_style(chain: &Expr) -> syn::Result<(String, String)> {
+    let mut calls = vec![];
+    let mut current = chain;
+    loop {
+        match current {
+            Expr::MethodCall(method_call) => {
+                calls.push(method_call);
+                current = &*method_call.receiver;
+            }
+            Expr::Call(call) if is_ident_call(call, "s") && call.args.is_empty() => break,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    current,
+                    "static_style! chains must start with `s()`",
+                ))
+            }
+        }
+    }
+    calls.reverse();
+
+    let mut name = String::new();
+    let mut media = None;
+    let mut pseudo = String::new();
+    let mut rules = String::new();
+
+    for call in calls {
+        let method = call.method.to_string();
+        let args = call.args.iter().collect::<Vec<&Expr>>();
+
+        match (method.as_str(), args.as_slice()) {
+            ("name", [arg]) => name = static_literal_value(arg)?,
+            ("media", [arg]) => media = Some(static_literal_value(arg)?),
+            (pseudo_method, []) if STATIC_PSEUDOS.contains(&pseudo_method) => {
+                let prefix = if pseudo_method == "before" || pseudo_method == "after" {
+                    "::"
+                } else {
+                    ":"
+                };
+                pseudo = format!("{}{}", prefix, pseudo_method.to_kebab_case());
+            }
+            (property, [arg]) if STATIC_PROPERTIES.contains(&property.to_kebab_case().as_str()) => {
+                rules.push_str(&format!(
+                    "{}: {};\n",
+                    property.to_kebab_case(),
+                    static_literal_value(arg)?
+                ))
+            }
+            (property, [_]) => {
+                return Err(syn::Error::new_spanned(
+                    &call.method,
+                    format!(
+                        "static_style! does not know the css property `{}`",
+                        property.to_kebab_case()
+                    ),
+                ))
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &call.method,
+                    format!(
+                        "static_style! cannot evaluate `{}` at compile time, use a runtime style instead",
+                        method
+                    ),
+                ))
+            }
+        }
+    }
+
+    let body = format!("{}{{\n{}}}", pseudo, rules);
+    let hash = fnv1a_64(&format!("{}{}", media.as_deref().unwrap_or(""), body));
+    let class_name = if name.is_empty() {
+        format!("seedstatic-{:x}", hash)
+    } else {
+        format!("seedstatic-{}-{:x}", name, hash)
+    };
+
+    let full_css = match media {
+        Some(media) => format!("{}{{\n.{}{}}}\n", media, class_name, body),
+        None => format!(".{}{}\n", class_name, body),
+    };
+    Ok((class_name, full_css))
+}
+
+// The css properties rendered by the runtime `Style` methods.
+const STATIC_PROPERTIES: &[&str] = &[
+    "align-content",
+    "align-items",
+    "align-self",
+    "animation",
+    "animation-delay",
+    "animation-direction",
+    "animation-duration",
+    "animation-fill-mode",
+    "animation-iteration-count",
+    "animation-name",
+    "animation-play-state",
+    "animation-timing-function",
+    "backface-visibility",
+    "background",
+    "background-attachment",
+    "background-blend-mode",
+    "background-clip",
+    "background-color",
+    "background-image",
+    "background-origin",
+    "background-position",
+    "background-repeat",
+    "background-size",
+    "border",
+    "border-bottom",
+    "border-bottom-color",
+    "border-bottom-left-radius",
+    "border-bottom-right-radius",
+    "border-bottom-style",
+    "border-bottom-width",
+    "border-collapse",
+    "border-color",
+    "border-image",
+    "border-image-outset",
+    "border-image-repeat",
+    "border-image-slice",
+    "border-image-source",
+    "border-image-width",
+    "border-left",
+    "border-left-color",
+    "border-left-style",
+    "border-left-width",
+    "border-radius",
+    "border-right",
+    "border-right-color",
+    "border-right-style",
+    "border-right-width",
+    "border-spacing",
+    "border-style",
+    "border-top",
+    "border-top-color",
+    "border-top-left-radius",
+    "border-top-right-radius",
+    "border-top-style",
+    "border-top-width",
+    "border-width",
+    "bottom",
+    "box-decoration-break",
+    "box-shadow",
+    "box-sizing",
+    "break-after",
+    "break-before",
+    "break-inside",
+    "caption-side",
+    "caret-color",
+    "clear",
+    "clip",
+    "clip-path",
+    "color",
+    "column-count",
+    "column-fill",
+    "column-gap",
+    "column-rule",
+    "column-rule-color",
+    "column-rule-style",
+    "column-rule-width",
+    "column-span",
+    "column-width",
+    "columns",
+    "container-name",
+    "container-type",
+    "content",
+    "counter-increment",
+    "counter-reset",
+    "cursor",
+    "direction",
+    "display",
+    "empty-cells",
+    "fill",
+    "filter",
+    "flex",
+    "flex-basis",
+    "flex-direction",
+    "flex-flow",
+    "flex-grow",
+    "flex-shrink",
+    "flex-wrap",
+    "float",
+    "font",
+    "font-family",
+    "font-feature-settings",
+    "font-kerning",
+    "font-language-override",
+    "font-size",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-synthesis",
+    "font-variant",
+    "font-variant-alternates",
+    "font-variant-caps",
+    "font-variant-east-asian",
+    "font-variant-ligatures",
+    "font-variant-numeric",
+    "font-variant-position",
+    "font-weight",
+    "gap",
+    "grid",
+    "grid-area",
+    "grid-auto-columns",
+    "grid-auto-flow",
+    "grid-auto-rows",
+    "grid-column",
+    "grid-column-end",
+    "grid-column-gap",
+    "grid-column-start",
+    "grid-gap",
+    "grid-row",
+    "grid-row-end",
+    "grid-row-gap",
+    "grid-row-start",
+    "grid-template",
+    "grid-template-areas",
+    "grid-template-columns",
+    "grid-template-rows",
+    "height",
+    "hyphens",
+    "image-rendering",
+    "isolation",
+    "justify-content",
+    "justify-items",
+    "justify-self",
+    "left",
+    "letter-spacing",
+    "line-break",
+    "line-height",
+    "list-style",
+    "list-style-image",
+    "list-style-position",
+    "list-style-type",
+    "margin",
+    "margin-bottom",
+    "margin-left",
+    "margin-right",
+    "margin-top",
+    "mask",
+    "mask-type",
+    "max-height",
+    "max-width",
+    "min-height",
+    "min-width",
+    "mix-blend-mode",
+    "object-fit",
+    "object-position",
+    "opacity",
+    "order",
+    "orphans",
+    "outline",
+    "outline-color",
+    "outline-style",
+    "outline-width",
+    "overflow",
+    "overflow-wrap",
+    "overflow-x",
+    "overflow-y",
+    "padding",
+    "padding-bottom",
+    "padding-left",
+    "padding-right",
+    "padding-top",
+    "page-break",
+    "page-break-after",
+    "page-break-before",
+    "page-break-inside",
+    "perspective",
+    "perspective-origin",
+    "place-content",
+    "pointer-events",
+    "position",
+    "quotes",
+    "resize",
+    "right",
+    "row-gap",
+    "scroll-behavior",
+    "shape-image-threshold",
+    "shape-margin",
+    "stroke",
+    "tab-size",
+    "table-layout",
+    "text-align",
+    "text-align-last",
+    "text-combine-upright",
+    "text-decoration",
+    "text-decoration-color",
+    "text-decoration-line",
+    "text-decoration-style",
+    "text-emphasis",
+    "text-emphasis-color",
+    "text-emphasis-position",
+    "text-emphasis-style",
+    "text-indent",
+    "text-justify",
+    "text-orientation",
+    "text-overflow",
+    "text-shadow",
+    "text-transform",
+    "text-underline-position",
+    "top",
+    "touch-action",
+    "transform",
+    "transform-origin",
+    "transform-style",
+    "transition",
+    "transition-delay",
+    "transition-duration",
+    "transition-property",
+    "transition-timing-function",
+    "unicode-bidi",
+    "user-select",
+    "vertical-align",
+    "visibility",
+    "white-space",
+    "widows",
+    "width",
+    "will-change",
+    "word-break",
+    "word-spacing",
+    "word-wrap",
+    "writing-mode",
+    "z-index",
+];
+
+const STATIC_PSEUDOS: &[&str] = &[
+    "active",
+    "checked",
+    "disabled",
+    "empty",
+    "enabled",
+    "first_child",
+    "first_of_type",
+    "focus",
+    "hover",
+    "in_range",
+    "invalid",
+    "last_child",
+    "last_of_type",
+    "link",
+    "only_of_type",
+    "only_child",
+    "optional",
+    "out_of_range",
+    "read_only",
+    "read_write",
+    "required",
+    "root",
+    "target",
+    "valid",
+    "visited",
+    "before",
+    "after",
+];
+
+fn is_ident_call(call: &syn::ExprCall, name: &str) -> bool {
+    if let Expr::Path(path) = &*call.func {
+        path.path.is_ident(name)
+    } else {
+        false
+    }
+}
+
+fn static_number_value(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => Ok(lit.base10_digits().to_string()),
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Float(lit),
+            ..
+        }) => Ok(lit.base10_digits().to_string()),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "static_style! measures must be number literals",
+        )),
+    }
+}
+
+fn static_literal_value(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok(lit.value()),
+        Expr::Lit(_) => static_number_value(expr),
+        Expr::Call(call) if call.args.len() == 1 => {
+            let value = static_number_value(call.args.first().unwrap())?;
+            let unit = ["px", "rem", "em", "vw", "vh", "cm", "pc"]
+                .iter()
+                .find(|unit| is_ident_call(call, unit))
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        &call.func,
+                        "static_style! only supports px, rem, em, vw, vh, cm and pc measures",
+                    )
+                })?;
+            if *unit == "pc" {
+                Ok(format!("{}%", value))
+            } else {
+                Ok(format!("{}{}", value, unit))
+            }
+        }
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "static_style! arguments must be literals",
+        )),
+    }
+}
+
+// FNV-1a, unlike DefaultHasher its output is guaranteed not to change between compiler versions.
+fn fnv1a_64(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
 // #[track_caller]
 // pub fn hover(&self) -> Style {
 //     let new_style = self.clone();
@@ -588,6 +1086,312 @@ pub fn generate_from_strs(input: TokenStream) -> TokenStream {
 // }
 ////
 //
+// Derive Proc Macro for theme alias enums.
+//
+// #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+// #[theme(color)]
+// enum Brand {
+//     #[theme(value = "#FFF5F5")]
+//     Primary,
+//     #[theme(space, value = "4px")]
+//     Gutter,
+//     #[theme(breakpoint, min = 0, max = 599)]
+//     Small,
+// }
+//
+// implements the marker traits of every kind used (here `ColorTheme`, `SpaceTheme` and
+// `BreakpointTheme`) and `ThemeAlias`, whose `register_defaults(theme)` sets every value.
+//
+// Variants without a value are reported with a warning, or an error with `#[theme(strict)]`,
+// unless no variant has a value at all.
+//
+// The generated impls name the library as the deriving crate's Cargo.toml does, so it works
+// whether or not `seed_style_preview` is renamed there.
+
+#[derive(Default)]
+struct ThemeAttr {
+    kinds: Vec<syn::Ident>,
+    value: Option<syn::LitStr>,
+    min: Option<syn::LitInt>,
+    max: Option<syn::LitInt>,
+    strict: bool,
+}
+
+impl ThemeAttr {
+    fn has_value(&self) -> bool {
+        self.value.is_some() || self.min.is_some()
+    }
+}
+
+fn parse_theme_attrs(attrs: &[syn::Attribute]) -> syn::Result<ThemeAttr> {
+    let mut theme_attr = ThemeAttr::default();
+
+    for attr in attrs.iter().filter(|a| a.path.is_ident("theme")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `#[theme(kind, value = \"..\")]`",
+                ))
+            }
+        };
+
+        for nested in list.nested {
+            match nested {
+                syn::NestedMeta::Meta(Meta::Path(path)) => {
+                    let ident = path
+                        .get_ident()
+                        .cloned()
+                        .ok_or_else(|| syn::Error::new_spanned(&path, "expected a theme kind"))?;
+                    if ident == "strict" {
+                        theme_attr.strict = true;
+                    } else if theme_kind(&ident.to_string()).is_some() {
+                        theme_attr.kinds.push(ident);
+                    } else {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "unknown theme kind, expected one of color, space, size, font_size, \
+                             shadow, border, border_width, border_style, radius, transition, \
+                             line_height, letter_spacing, breakpoint, font, z_index, display, style",
+                        ));
+                    }
+                }
+                syn::NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) => {
+                    let key = path.get_ident().map(|i| i.to_string()).unwrap_or_default();
+                    match (key.as_str(), lit) {
+                        ("value", Lit::Str(s)) => theme_attr.value = Some(s),
+                        ("min", Lit::Int(i)) => theme_attr.min = Some(i),
+                        ("max", Lit::Int(i)) => theme_attr.max = Some(i),
+                        (_, lit) => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `value = \"..\"`, `min = ..` or `max = ..`",
+                            ))
+                        }
+                    }
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "expected `#[theme(kind, value = \"..\")]`",
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(theme_attr)
+}
+
+// The path of this library as the calling crate names it, `seed_style_preview` unless it is
+// renamed in its Cargo.toml. The library itself refers to its own items as `::seed_style`.
+fn seed_style_path() -> proc_macro2::TokenStream {
+    match proc_macro_crate::crate_name("seed_style_preview") {
+        Ok(proc_macro_crate::FoundCrate::Name(name)) => {
+            let name = format_ident!("{}", name);
+            quote!(::#name)
+        }
+        Ok(proc_macro_crate::FoundCrate::Itself) | Err(_) => quote!(::seed_style),
+    }
+}
+
+// theme kind -> (marker trait, Theme setter)
+fn theme_kind(kind: &str) -> Option<(&'static str, Option<&'static str>)> {
+    Some(match kind {
+        "color" => ("ColorTheme", Some("set_color")),
+        "space" => ("SpaceTheme", Some("set_space")),
+        "size" => ("SizeTheme", Some("set_size")),
+        "font_size" => ("FontSizeTheme", Some("set_font_size")),
+        "shadow" => ("ShadowTheme", Some("set_shadow")),
+        "border" => ("BorderTheme", Some("set_border")),
+        "border_width" => ("BorderWidthTheme", Some("set_border_width")),
+        "border_style" => ("BorderStyleTheme", Some("set_border_style")),
+        "radius" | "border_radius" => ("BorderRadiusTheme", Some("set_border_radius")),
+        "transition" => ("TransitionTheme", Some("set_transition")),
+        "line_height" => ("LineHeightTheme", Some("set_line_height")),
+        "letter_spacing" => ("LetterSpacingTheme", Some("set_letter_spacing")),
+        "breakpoint" => ("BreakpointTheme", Some("set_breakpoint")),
+        "font" => ("FontTheme", None),
+        "z_index" => ("ZIndexTheme", None),
+        "display" => ("DisplayTheme", None),
+        "style" => ("StyleTheme", None),
+        _ => return None,
+    })
+}
+
+#[proc_macro_derive(ThemeAlias, attributes(theme))]
+pub fn derive_theme_alias(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_theme_alias(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_theme_alias(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let data_enum = if let syn::Data::Enum(data_enum) = &input.data {
+        data_enum
+    } else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ThemeAlias can only be derived for enums",
+        ));
+    };
+
+    let enum_attr = parse_theme_attrs(&input.attrs)?;
+
+    let mut marker_traits: Vec<String> = vec![];
+    let mut kind_names: Vec<String> = vec![];
+    let mut variant_idents = vec![];
+    let mut registrations = vec![];
+    let mut missing = vec![];
+
+    for variant in &data_enum.variants {
+        if variant.fields != syn::Fields::Unit {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "ThemeAlias variants cannot have fields",
+            ));
+        }
+        let variant_ident = &variant.ident;
+        variant_idents.push(variant_ident.clone());
+
+        let variant_attr = parse_theme_attrs(&variant.attrs)?;
+        let kinds = if variant_attr.kinds.is_empty() {
+            &enum_attr.kinds
+        } else {
+            &variant_attr.kinds
+        };
+
+        if kinds.is_empty() {
+            return Err(syn::Error::new_spanned(
+                variant_ident,
+                "no theme kind, add `#[theme(color)]` to the enum or the variant",
+            ));
+        }
+
+        for kind in kinds {
+            let (marker_trait, setter) = theme_kind(&kind.to_string()).unwrap();
+            if !marker_traits.iter().any(|t| t == marker_trait) {
+                marker_traits.push(marker_trait.to_string());
+            }
+            if !kind_names.contains(&kind.to_string()) {
+                kind_names.push(kind.to_string());
+            }
+
+            if !variant_attr.has_value() {
+                continue;
+            }
+
+            let setter = if let Some(setter) = setter {
+                format_ident!("{}", setter)
+            } else {
+                return Err(syn::Error::new_spanned(
+                    kind,
+                    "this theme kind cannot have a default value",
+                ));
+            };
+
+            if kind == "breakpoint" {
+                let min = variant_attr.min.as_ref().ok_or_else(|| {
+                    syn::Error::new_spanned(kind, "breakpoints take `min = ..` and `max = ..`")
+                })?;
+                let max = if let Some(max) = &variant_attr.max {
+                    quote! { Some(#max) }
+                } else {
+                    quote! { None }
+                };
+                registrations.push(quote! {
+                    let theme = theme.#setter(#name::#variant_ident, (#min, #max));
+                });
+            } else if let Some(value) = &variant_attr.value {
+                registrations.push(quote! {
+                    let theme = theme.#setter(#name::#variant_ident, #value);
+                });
+            } else {
+                return Err(syn::Error::new_spanned(
+                    kind,
+                    "expected `value = \"..\"`",
+                ));
+            }
+        }
+
+        if !variant_attr.has_value() {
+            missing.push(variant_ident.clone());
+        }
+    }
+
+    let mut report = quote! {};
+    if !registrations.is_empty() || enum_attr.strict {
+        for variant_ident in &missing {
+            let message = format!(
+                "`{}::{}` has no theme value, add `value = \"..\"` to its `#[theme(..)]` attribute",
+                name, variant_ident
+            );
+            if enum_attr.strict {
+                let error = syn::Error::new_spanned(variant_ident, message).to_compile_error();
+                report = quote! { #report #error };
+            } else {
+                // there is no stable api for proc macro warnings, a deprecated item is used instead.
+                let warning_ident = format_ident!(
+                    "{}_{}_has_no_theme_value",
+                    name,
+                    variant_ident,
+                    span = variant_ident.span()
+                );
+                report = quote! {
+                    #report
+                    const _: () = {
+                        #[deprecated(note = #message)]
+                        #[allow(non_camel_case_types)]
+                        struct #warning_ident;
+                        let _ = #warning_ident;
+                    };
+                };
+            }
+        }
+    }
+
+    let seed_style = seed_style_path();
+    let marker_impls = marker_traits.iter().map(|marker_trait| {
+        let marker_trait = format_ident!("{}", marker_trait);
+        quote! {
+            impl #impl_generics #seed_style::#marker_trait for #name #ty_generics #where_clause {}
+        }
+    });
+
+    Ok(quote! {
+        #(#marker_impls)*
+
+        impl #impl_generics #seed_style::ThemeAlias for #name #ty_generics #where_clause {
+            fn variants() -> ::std::vec::Vec<Self> {
+                ::std::vec![#(#name::#variant_idents),*]
+            }
+
+            fn alias_name(&self) -> &'static str {
+                match self {
+                    #(#name::#variant_idents => stringify!(#variant_idents),)*
+                }
+            }
+
+            fn theme_kinds() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#kind_names),*]
+            }
+
+            fn register_defaults(theme: #seed_style::Theme) -> #seed_style::Theme {
+                #(#registrations)*
+                theme
+            }
+        }
+
+        #report
+    })
+}
+
 // Proc macro to generate Css Values from theme variants
 //
 // [ (ThemeId, GenericTypeName, SpecificTypeName )]
@@ -660,7 +1464,7 @@ pub fn generate_froms(input: TokenStream) -> self::proc_macro::TokenStream {
                                 It: DoubleEndedIterator<Item = &'a Theme>,
                             {
                                 // log!(self.0.clone()); // print theme key if needed for debug
-                                it.rev().find_map(|theme| theme.get::<T, #generic_ident>(self.0.clone())).map(|v| v.into())
+                                it.rev().find_map(|theme| theme.get::<T, #generic_ident>(self.0.clone()).map(|v| theme.alias_value_or_css_var(&self.0, v))).map(|v| v.into())
                             }
                         }
 
@@ -672,13 +1476,13 @@ pub fn generate_froms(input: TokenStream) -> self::proc_macro::TokenStream {
                             where
                                 It: DoubleEndedIterator<Item = &'a Theme>,
                             {
-                                it.rev().find_map( |theme| theme.#theme_scale_ident.get(self.0.clone())).cloned().map(|v| v.into())
+                                it.rev().find_map( |theme| theme.#theme_scale_ident.get(self.0.clone()).cloned().map(|v| theme.scale_value_or_css_var(self.0, v))).map(|v| v.into())
                             }
                         }
 
                             impl <T> From<T> for #specific_ident where T:#themeid_ident + 'static{
                                 fn from(v: T) -> Self {
-                                    with_themes( #struct_type(v)).expect("chchhh")
+                                    with_themes( #struct_type(v.clone())).unwrap_or_else(|| missing_theme_value::<T, #specific_ident>(&v))
                                 }
                             }
 
@@ -723,8 +1527,7 @@ pub fn generate_froms(input: TokenStream) -> self::proc_macro::TokenStream {
                                             theme_value.clone()
 
                                         } else {
-                                            panic!("Theme scale does not exist {}", #theme_scale_string )
-
+                                            missing_theme_scale_value::<#specific_ident>(#theme_scale_string, self)
                                         };
                                     style.add_rule(Box::new(theme_value));
                                 }
@@ -845,42 +1648,33 @@ pub fn generate_froms(input: TokenStream) -> self::proc_macro::TokenStream {
 
 
                                         let mut old_style = None;
+                                        let mut media_rules: Vec<(String, Box<dyn CssValueTrait>)> = vec![];
 
                                         for (style_idx, bp) in bp_scale.iter().enumerate(){
                                             if let Some(theme_idx) = self.get(style_idx){
-                                            if let Some(generic_value )= with_themes(ReturnThemeValFromUsize(*theme_idx, PhantomData::<#specific_ident>)) {
+                                                    let specific_value : #specific_ident =
+                                                        if let Some(generic_value )= with_themes(ReturnThemeValFromUsize(*theme_idx, PhantomData::<#specific_ident>)) {
+                                                            generic_value.into()
+                                                        } else {
+                                                            missing_theme_scale_value::<#specific_ident>(#theme_scale_string, *theme_idx)
+                                                        };
 
-                                                    let specific_value : #specific_ident = generic_value.into();
-
-                                                    let rules = style
-                                                                    .media_rules
-                                                                    .entry(bp.clone().0)
-                                                                    .or_insert(vec![]);
-                                                    rules.push(Rule {
-                                                        value: Box::new(specific_value.clone()),
-                                                    });
+                                                    media_rules.push((bp.render(), Box::new(specific_value.clone())));
 
                                                     old_style = Some(specific_value);
-                                                }else {
-                                                    panic!("No theme scale for that index!")
-                                                }
 
-                                            } else {
-                                                    let rules = style
-                                                        .media_rules
-                                                        .entry(bp.clone().0)
-                                                        .or_insert(vec![]);
-                                                        rules.push(Rule {
-                                                            value: Box::new(old_style.clone().unwrap()),
-                                                        });
+                                            } else if let Some(old_style) = &old_style {
+                                                    media_rules.push((bp.render(), Box::new(old_style.clone())));
 
                                                 }
 
 
                                         }
-                                    } else {
-                                        panic!("No breakpoints have been defined!")
-
+                                        style.add_media_rules(media_rules);
+                                    } else if let Some(theme_idx) = self.first() {
+                                        // without breakpoints only the first value applies
+                                        missing_breakpoints();
+                                        <usize as UpdateStyle<#specific_ident>>::update_style(*theme_idx, style);
                                     }
                             }
                         }
@@ -2534,3 +3328,65 @@ pub fn process_submacro_part(input: TokenStream) -> TokenStream {
         #node_name(Node::Element(eld))
     }).into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_static_styles() {
+        let chain = syn::parse_str::<Expr>(
+            r##"s().name("card").padding(px(8)).color("#333").hover().media("@media print")"##,
+        )
+        .unwrap();
+        let (class_name, css) = expand_static_style(&chain).unwrap();
+        assert!(class_name.starts_with("seedstatic-card-"));
+        assert_eq!(
+            css,
+            format!(
+                "@media print{{\n.{}:hover{{\npadding: 8px;\ncolor: #333;\n}}}}\n",
+                class_name
+            )
+        );
+        assert_eq!(expand_static_style(&chain).unwrap().0, class_name);
+
+        let typo = syn::parse_str::<Expr>(r##"s().colour("red")"##).unwrap();
+        assert!(expand_static_style(&typo)
+            .unwrap_err()
+            .to_string()
+            .contains("`colour`"));
+        let runtime = syn::parse_str::<Expr>("s().color(theme_color)").unwrap();
+        assert!(expand_static_style(&runtime).is_err());
+    }
+
+    #[test]
+    fn gives_static_styles_in_media_queries_their_own_class() {
+        let plain = syn::parse_str::<Expr>("s().padding(px(8))").unwrap();
+        let print = syn::parse_str::<Expr>(r#"s().padding(px(8)).media("@media print")"#).unwrap();
+        assert_ne!(
+            expand_static_style(&plain).unwrap().0,
+            expand_static_style(&print).unwrap().0
+        );
+    }
+
+    #[test]
+    fn keeps_static_css_of_earlier_compilations() {
+        let out_dir =
+            std::env::temp_dir().join(format!("seed_style_static_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&out_dir);
+        std::fs::create_dir_all(&out_dir).unwrap();
+        std::env::set_var("OUT_DIR", &out_dir);
+
+        // each invocation as if expanded by a separate incremental compilation
+        for chain in &[r#"s().color("red")"#, r#"s().color("blue")"#] {
+            let chain = syn::parse_str::<Expr>(chain).unwrap();
+            let (class_name, css) = expand_static_style(&chain).unwrap();
+            write_static_css(&chain, &class_name, css).unwrap();
+        }
+
+        let css = std::fs::read_to_string(out_dir.join("seed_style_static.css")).unwrap();
+        assert!(css.contains("color: red;"));
+        assert!(css.contains("color: blue;"));
+        let _ = std::fs::remove_dir_all(&out_dir);
+    }
+}
@@ -1,4 +1,7 @@
 
+// lets macro expansions refer to `::seed_style::..` inside this crate as well
+extern crate self as seed_style;
+
 mod style;
 
 // exports
@@ -7,7 +10,7 @@ pub use style::CssValueTrait;
 // style builder
 pub use style::s;
 
-pub use seed_style_macros::{view_macro, as_tag, process_part, process_submacro_part,*};
+pub use seed_style_macros::{view_macro, as_tag, process_part, process_submacro_part, static_style, *};
 
 #[macro_export]
 macro_rules! with_dollar_sign {
@@ -18,9 +21,20 @@ macro_rules! with_dollar_sign {
 }
 
 
+// theme files
+#[cfg(feature = "theme-files")]
+pub use style::theme_files::{
+    ThemeAliases, ThemeFile, ThemeFileAliases, ThemeFileBreakpoint, ThemeFileError,
+    ThemeFileScales,
+};
+#[cfg(feature = "theme-files")]
+pub use style::design_tokens::{DesignTokensImport, UnmappedToken};
+
 // style property argument trait
 pub use style::{
-    PseudoTrait,
+    Pseudo, PseudoTrait, PseudoSelector, Nth,
+    // declarations and their flags
+    Rule, RuleFlags,
     // exports for Seed Layout
     composition::{default_breakpoint_theme, Composition, SeedBreakpoint, WithLayoutComposition},
     //ro col layout
@@ -35,26 +49,61 @@ pub use style::{
     measures::{cm, em, hsl, hsla,hsluv, hsluva, pc, px, rem, rgb, rgba, vh, vw, ExactLength},
     // presets
     presets::{seed_colors, default_colors_theme},
-    theme::change_theme_with_name,
+    theme::{change_theme_with_name, ThemeChange, ThemePatch},
+    // breakpoint tracking through media query listeners
+    breakpoints::{
+        current_breakpoint, notify_on_breakpoint_change, on_breakpoint_change,
+        media_query_matches, start_breakpoint_service, stop_breakpoint_service, BreakpointPair,
+    },
+    // typed media queries
+    media_query::{media, Contrast, Hover, MediaQuery, MediaType, Orientation, Pointer},
+    // feature queries
+    supports::{supports_decl, supports_selector, SupportsCondition},
+    // typed selectors
+    selector::{attr, attr_eq, class, element, id, this, Selector, SelectorCombinator},
+    // cascade layers
+    layers::{default_layer_order, layer_order, layered_css, render_layer_order, set_layer_order, CascadeLayer},
+    // light and dark theme variants following prefers-color-scheme
+    color_scheme::{
+        color_scheme, color_scheme_preference, load_color_scheme_themes,
+        set_color_scheme_preference, system_prefers_dark, ColorScheme, ColorSchemePreference,
+    },
     
-    theme::{app_themes, load_app_themes},
+    theme::{activate_theme_css_vars, app_themes, load_app_theme, load_app_themes},
+    // scoped themes for subtrees
+    theme::{load_scoped_themes, scoped_themes, with_theme_provider},
     // themes
     theme::Theme,
     // themes, conditional rendering
-    theme::{except, only, only_and_above, only_and_below, at_breakpoint_and_above},
+    theme::{except, only, only_and_above, only_and_below, at_breakpoint_and_above, when_media},
     //theme alias keys
     theme::{
         BorderRadiusTheme, BorderStyleTheme, BorderTheme, BorderWidthTheme, BreakpointTheme,
         ColorTheme, DisplayTheme, FontSizeTheme, FontTheme, LetterSpacingTheme, LineHeightTheme,
         ShadowTheme, SizeTheme, SpaceTheme, StyleTheme, TransitionTheme, ZIndexTheme,
     },
+    // implemented by #[derive(ThemeAlias)]
+    theme::ThemeAlias,
+    // theme completeness checks and fallbacks for missing aliases
+    theme_validation::{
+        debug_check_app_themes, empty_app_theme_scales, register_alias_names, set_theme_fallback,
+        theme_fallback, validate_app_themes, MissingThemeValue, ThemeFallback,
+    },
     AddStyleToNode,
+    // stylesheet backends, headless rendering
+    stylesheet::{
+        collect_unused_styles, rendered_css, rendered_html_class, use_css_registry,
+        use_stylesheet_backend, with_stylesheet_backend, DomBackend, InMemoryBackend,
+        ShadowRootBackend, StringBufferBackend, StyleBookkeeping, StyleSheetBackend,
+    },
     // global style api
     GlobalStyle,
     // extension trait to allow Style structs to be update_el processed by seed.
     LocalUpdateEl,LocalUpdateElForIterator,
     // Style struct,  technically user shouldn't really need to access this directly
     Style,
+    // class generated at compile time by `static_style!`
+    StaticStyle,
     // style property argument trait
     UpdateStyle,
 };
@@ -81,4 +130,858 @@ mod tests {
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn renders_styles_without_a_dom() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        let mut node: Node<()> = div![];
+        node.style(s().color("red"));
+
+        assert!(rendered_css().contains(".sst-class.seedstyle-"));
+        assert!(rendered_css().contains("color: red;"));
+    }
+
+    #[test]
+    fn renders_custom_properties() {
+        use crate::*;
+
+        let css = s()
+            .var("brand", "#f00")
+            .var("--gap", px(4))
+            .color(var("brand"))
+            .padding(var("gap").fallback(px(8)))
+            .render();
+
+        assert!(css.contains("--brand: #f00;"));
+        assert!(css.contains("--gap: 4px;"));
+        assert!(css.contains("color: var(--brand);"));
+        assert!(css.contains("padding: var(--gap, 8px);"));
+    }
+
+    #[test]
+    fn keeps_global_rules_first_after_switching_backends() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        GlobalStyle::new()
+            .style("body", s().margin(px(0)))
+            .activate_styles();
+
+        use_css_registry();
+        let mut node: Node<()> = div![];
+        node.style(s().color("red"));
+        GlobalStyle::new()
+            .style("p", s().margin(px(4)))
+            .activate_styles();
+
+        let css = rendered_css();
+        assert!(!css.contains(" body{"));
+        assert!(css.find(" p{").unwrap() < css.find("color: red;").unwrap());
+    }
+
+    #[test]
+    fn collects_unused_styles() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        let view = || {
+            let mut node: Node<()> = div![];
+            node.style(s().color("red"));
+        };
+
+        use_css_registry();
+        view();
+        assert_eq!(collect_unused_styles(), 0);
+        assert!(rendered_css().contains("color: red;"));
+
+        assert_eq!(collect_unused_styles(), 1);
+        assert!(!rendered_css().contains("color: red;"));
+
+        view();
+        assert!(rendered_css().contains("color: red;"));
+    }
+
+    #[test]
+    fn keeps_styles_of_mounted_elements() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        let skipped = || {
+            let mut node: Node<()> = div![];
+            node.style(s().color("red"));
+            node
+        };
+        let removed = || {
+            let mut node: Node<()> = div![];
+            node.style(s().color("blue"));
+            node
+        };
+
+        use_css_registry();
+        let classes = match skipped() {
+            Node::Element(el) => match el.attrs.vals.get(&At::Class) {
+                Some(AtValue::Some(classes)) => classes.clone(),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+
+        use_stylesheet_backend(InMemoryBackend::default().with_mounted_classes(vec![classes]));
+        skipped();
+        removed();
+        assert_eq!(collect_unused_styles(), 0);
+
+        // the next render skips `skipped`, which stays in the document, and drops `removed`
+        assert_eq!(collect_unused_styles(), 1);
+        assert!(rendered_css().contains("color: red;"));
+        assert!(!rendered_css().contains("color: blue;"));
+    }
+
+    #[test]
+    fn renders_theme_as_css_variables() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone, Debug)]
+        enum Brand {
+            Primary,
+            DarkAccent,
+        }
+        impl ColorTheme for Brand {}
+
+        let theme = Theme::new("brand")
+            .css_vars(":root")
+            .css_var_alias::<Brand>()
+            .set_color(Brand::Primary, CssColor::Hex(0xff0000))
+            .set_color(Brand::DarkAccent, "#222")
+            .space_scale(&[px(0), px(4)]);
+
+        let block = theme.render_css_vars().unwrap();
+        assert!(block.starts_with(":root {"));
+        assert!(block.contains("--color-primary: #ff0000;"));
+        assert!(block.contains("--color-dark-accent: #222;"));
+        assert!(block.contains("--space-1: 4px;"));
+    }
+
+    #[test]
+    fn looks_up_themed_properties_as_css_variables() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone, Debug)]
+        enum Brand {
+            Primary,
+        }
+        impl ColorTheme for Brand {}
+
+        app_themes().update(|themes| {
+            themes.push(
+                Theme::new("brand")
+                    .css_vars(":root")
+                    .css_var_alias::<Brand>()
+                    .set_color(Brand::Primary, "#ff0000")
+                    .space_scale(&[px(0), px(4)]),
+            )
+        });
+
+        let css = s().color(Brand::Primary).padding(1usize).render();
+        assert!(css.contains("color: var(--color-primary);"));
+        assert!(css.contains("padding: var(--space-1);"));
+        assert!(!css.contains("#ff0000"));
+    }
+
+    #[cfg(feature = "theme-files")]
+    #[test]
+    fn round_trips_theme_files() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone)]
+        enum Brand {
+            Primary,
+        }
+        impl ColorTheme for Brand {}
+
+        let aliases = ThemeAliases::new().color("primary", Brand::Primary);
+        let json = r##"{
+            "name": "brand",
+            "scales": { "spaces": ["0px", "4px"], "breakpoints": [600, 900] },
+            "aliases": { "colors": { "primary": "#ff0000", "unknown": "#000" } }
+        }"##;
+
+        let theme = Theme::from_json(json, &aliases).unwrap();
+        assert_eq!(theme.breakpoints_scale, vec![600, 900]);
+        assert_eq!(theme.spaces_scale.len(), 2);
+
+        let file = ThemeFile::from_theme(&theme, &aliases);
+        assert_eq!(file.aliases.colors.get("primary").unwrap(), "#ff0000");
+        assert!(file.aliases.colors.get("unknown").is_none());
+
+        let toml = theme.to_toml(&aliases).unwrap();
+        let reloaded = Theme::from_toml(&toml, &aliases).unwrap();
+        assert_eq!(reloaded.name, "brand");
+        assert_eq!(reloaded.spaces_scale.len(), 2);
+    }
+
+    #[cfg(feature = "theme-files")]
+    #[test]
+    fn imports_design_tokens() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone)]
+        enum Brand {
+            Primary,
+            Gutter,
+        }
+        impl ColorTheme for Brand {}
+        impl SpaceTheme for Brand {}
+
+        let aliases = ThemeAliases::new()
+            .color("color.brand.primary", Brand::Primary)
+            .space("space.gutter", Brand::Gutter);
+        let tokens = r##"{
+            "color": {
+                "$type": "color",
+                "base": { "red": { "$value": "#ff0000" } },
+                "brand": { "primary": { "$value": "{color.base.red}" } }
+            },
+            "space": { "gutter": { "$type": "dimension", "$value": "16px" } },
+            "breakpoint": {
+                "$type": "dimension",
+                "md": { "$value": "900px" },
+                "sm": { "$value": "600px" }
+            },
+            "font": { "body": { "$type": "fontFamily", "$value": "Inter" } }
+        }"##;
+
+        let import = Theme::from_design_tokens("brand", tokens, &aliases).unwrap();
+        let theme = import.theme;
+        assert_eq!(theme.breakpoints_scale, vec![600, 900]);
+        assert_eq!(
+            ThemeFile::from_theme(&theme, &aliases).aliases.colors["color.brand.primary"],
+            "#ff0000"
+        );
+
+        let unmapped = import
+            .unmapped
+            .iter()
+            .map(|t| t.path.as_str())
+            .collect::<Vec<_>>();
+        assert!(unmapped.contains(&"font.body"));
+        assert!(unmapped.contains(&"color.base.red"));
+        assert!(!unmapped.contains(&"space.gutter"));
+    }
+
+    #[cfg(feature = "theme-files")]
+    #[test]
+    fn exports_themes() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone)]
+        enum Brand {
+            Primary,
+        }
+        impl ColorTheme for Brand {}
+
+        let aliases = ThemeAliases::new().color("color.primary", Brand::Primary);
+        let theme = Theme::new("brand")
+            .set_color(Brand::Primary, "#ff0000")
+            .space_scale(&[px(0), px(4)]);
+
+        let css = theme.export_css_vars(&aliases, ":root");
+        assert!(css.contains("--color-primary: #ff0000;"));
+        assert!(css.contains("--space-1: 4px;"));
+
+        let scss = theme.export_scss(&aliases);
+        assert!(scss.contains("$colors: (\n  \"color.primary\": #ff0000\n);"));
+
+        assert!(theme
+            .export_typescript(&aliases)
+            .starts_with("export const theme = {"));
+
+        let tokens = theme.export_design_tokens(&aliases).unwrap();
+        let import = Theme::from_design_tokens("copy", &tokens, &aliases).unwrap();
+        assert_eq!(
+            ThemeFile::from_theme(&import.theme, &aliases).aliases.colors["color.primary"],
+            "#ff0000"
+        );
+    }
+
+    #[cfg(feature = "theme-files")]
+    #[test]
+    fn rejects_colliding_design_token_keys() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone)]
+        enum Gap {
+            Half,
+        }
+        impl SpaceTheme for Gap {}
+
+        let aliases = ThemeAliases::new().space("0.5", Gap::Half);
+        let theme = Theme::new("gaps")
+            .space_scale(&[px(0), px(4)])
+            .set_space(Gap::Half, px(2));
+        match theme.export_design_tokens(&aliases) {
+            Err(ThemeFileError::DesignTokenCollision(path)) => assert_eq!(path, "space.0.5"),
+            other => panic!("expected a collision, got {:?}", other),
+        }
+
+        let aliases = ThemeAliases::new().space("half", Gap::Half);
+        assert!(theme.export_design_tokens(&aliases).is_ok());
+    }
+
+    #[test]
+    fn validates_theme_aliases() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+        #[theme(color)]
+        enum Brand {
+            Primary,
+            Accent,
+        }
+
+        let theme = Theme::new("brand").set_color(Brand::Primary, "#ff0000");
+        let missing = theme.validate::<Brand>();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].alias.as_deref(), Some("Accent"));
+        assert!(missing[0].value_type.ends_with("CssColor"));
+        assert!(theme.empty_scales().contains(&"spaces_scale"));
+
+        assert!(theme_fallback::<CssColor>().to_string().contains("unset"));
+        set_theme_fallback(CssColor::from("black"));
+        assert!(theme_fallback::<CssColor>().to_string().contains("black"));
+    }
+
+    #[test]
+    fn falls_back_on_missing_theme_values() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+        #[theme(color)]
+        enum Brand {
+            Primary,
+            Accent,
+        }
+
+        app_themes()
+            .update(|themes| themes.push(Theme::new("brand").set_color(Brand::Primary, "#ff0000")));
+
+        let css = s().color(Brand::Accent).padding(3usize).render();
+        assert!(css.contains("color: unset;"));
+        assert!(css.contains("padding: unset;"));
+
+        set_theme_fallback(CssColor::from("black"));
+        assert!(s().color(Brand::Accent).render().contains("color: black;"));
+    }
+
+    #[test]
+    fn extends_base_themes() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone)]
+        enum Brand {
+            Primary,
+            Accent,
+        }
+        impl ColorTheme for Brand {}
+
+        let light = Theme::new("light")
+            .set_color(Brand::Primary, "#ffffff")
+            .set_color(Brand::Accent, "#0000ff")
+            .space_scale(&[px(0), px(4)]);
+
+        let dark = Theme::new("dark")
+            .extend(&light)
+            .set_color(Brand::Primary, "#000000");
+        let color = |theme: &Theme, alias| theme.get::<Brand, CssColor>(alias).unwrap().to_string();
+        assert_eq!(dark.name, "dark");
+        assert!(color(&dark, Brand::Primary).contains("#000000"));
+        assert!(color(&dark, Brand::Accent).contains("#0000ff"));
+        assert_eq!(dark.spaces_scale.len(), 2);
+
+        let patched = light.with_overrides(Theme::new("").set_color(Brand::Accent, "#ff0000"));
+        assert_eq!(patched.name, "light");
+        assert!(color(&patched, Brand::Primary).contains("#ffffff"));
+        assert!(color(&patched, Brand::Accent).contains("#ff0000"));
+    }
+
+    #[test]
+    fn keeps_css_var_names_through_extends() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone, Debug)]
+        enum Brand {
+            Primary,
+        }
+        impl ColorTheme for Brand {}
+
+        let base = Theme::new("base")
+            .css_vars(":root")
+            .css_var_alias::<Brand>()
+            .css_var_alias::<Brand>()
+            .set_color(Brand::Primary, "#ffffff");
+        assert_eq!(base.alias_maps.len(), 2);
+        assert_eq!(base.css_var_aliases.len(), 1);
+
+        let dark = Theme::new("dark").extend(&base);
+        let high_contrast = Theme::new("high-contrast").extend(&dark);
+        let value =
+            high_contrast.alias_value_or_css_var(&Brand::Primary, CssColor::from("#ffffff"));
+        assert!(value.to_string().contains("var(--color-primary)"));
+        assert!(high_contrast
+            .render_css_vars()
+            .unwrap()
+            .contains("--color-primary: #ffffff;"));
+    }
+
+    #[test]
+    fn resolves_style_aliases_from_the_last_theme() {
+        use crate::*;
+
+        #[derive(Hash, PartialEq, Eq, Clone)]
+        enum Surface {
+            Card,
+        }
+        impl StyleTheme for Surface {}
+
+        app_themes().update(|themes| {
+            themes.push(Theme::new("base").set_style(Surface::Card, s().color("red")));
+            themes.push(Theme::new("brand").set_style(Surface::Card, s().color("green")));
+        });
+        scoped_themes().update(|themes| {
+            themes.push(Theme::new("promo").set_style(Surface::Card, s().color("blue")))
+        });
+
+        assert!(Style::from(Surface::Card).render().contains("green"));
+        let provided = with_theme_provider("promo", || Style::from(Surface::Card));
+        assert!(provided.render().contains("blue"));
+    }
+
+    #[test]
+    fn resolves_color_scheme_preferences() {
+        use crate::*;
+
+        assert_eq!(ColorSchemePreference::System.resolve(true), ColorScheme::Dark);
+        assert_eq!(ColorSchemePreference::System.resolve(false), ColorScheme::Light);
+        assert_eq!(ColorSchemePreference::Light.resolve(true), ColorScheme::Light);
+        for preference in &[
+            ColorSchemePreference::Light,
+            ColorSchemePreference::Dark,
+            ColorSchemePreference::System,
+        ] {
+            assert_eq!(ColorSchemePreference::parse(preference.as_str()), Some(*preference));
+        }
+        assert_eq!(ColorSchemePreference::parse("sepia"), None);
+    }
+
+    #[test]
+    fn builds_breakpoint_scale_queries() {
+        use crate::style::breakpoints::pair_media_query;
+        use crate::*;
+
+        let theme = Theme::new("bps").breakpoint_scale([600, 960]);
+        let queries = theme
+            .media_bp_pairs
+            .iter()
+            .map(|pair| pair_media_query(*pair).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            queries,
+            vec![
+                "(min-width: 0px) and (max-width: 599px)",
+                "(min-width: 600px) and (max-width: 959px)",
+                "(min-width: 960px)",
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_media_queries() {
+        use crate::*;
+
+        assert_eq!(
+            media().min_width(px(600)).hover(Hover::Hover).render(),
+            "@media (min-width: 600px) and (hover: hover)"
+        );
+        assert_eq!(
+            media().print().or(media().max_width(px(599))).to_string(),
+            "print, (max-width: 599px)"
+        );
+        assert_eq!(
+            (!media().screen().orientation(Orientation::Portrait)).to_string(),
+            "not screen and (orientation: portrait)"
+        );
+        assert_eq!(
+            media()
+                .prefers_reduced_motion()
+                .or(media().prefers_contrast(Contrast::More))
+                .and(media().screen())
+                .to_string(),
+            "screen and (prefers-reduced-motion: reduce), screen and (prefers-contrast: more)"
+        );
+        assert_eq!(
+            MediaQuery::from("@media (max-width: 10px)").render(),
+            "@media (max-width: 10px)"
+        );
+    }
+
+    #[test]
+    fn extends_raw_media_queries() {
+        use crate::*;
+
+        assert_eq!(
+            MediaQuery::from("print").min_width(px(600)).render(),
+            "@media print and (min-width: 600px)"
+        );
+        assert_eq!(
+            MediaQuery::from("(min-width: 600px)").screen().to_string(),
+            "screen and (min-width: 600px)"
+        );
+        assert_eq!(MediaQuery::from("@media print").screen().to_string(), "screen");
+        assert_eq!(
+            MediaQuery::from("print, (max-width: 599px)")
+                .min_width(px(300))
+                .to_string(),
+            "print and (min-width: 300px), (max-width: 599px) and (min-width: 300px)"
+        );
+    }
+
+    #[test]
+    fn combines_negated_and_conflicting_media_queries() {
+        use crate::*;
+
+        assert_eq!(
+            (!media().print()).and(media().min_width(px(600))).to_string(),
+            "screen and (min-width: 600px)"
+        );
+        assert_eq!(
+            (!media().screen().min_width(px(600)))
+                .and(media().hover(Hover::Hover))
+                .to_string(),
+            "print and (hover: hover), (not (min-width: 600px)) and (hover: hover)"
+        );
+        assert_eq!(media().screen().and(media().print()).to_string(), "not all");
+        assert_eq!(
+            media()
+                .screen()
+                .and(media().print())
+                .and(media().min_width(px(1)))
+                .to_string(),
+            "not all"
+        );
+    }
+
+    #[test]
+    fn renders_container_queries() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        let mut panel: Node<()> = div![];
+        panel.style(s().container("panel", CssContainerType::InlineSize));
+        let mut card: Node<()> = div![];
+        card.style(
+            s().color("blue")
+                .container_query("panel", media().min_width(px(400)).or(media().max_width(px(100)))),
+        );
+
+        let css = rendered_css();
+        assert!(css.contains("container-name: panel;"));
+        assert!(css.contains("container-type: inline-size;"));
+        assert!(css.contains("@container panel (min-width: 400px) or (max-width: 100px){\n.sst-class.seedstyle-"));
+    }
+
+    #[test]
+    fn renders_feature_queries() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        let condition = supports_decl("backdrop-filter", "blur(4px)")
+            .or(supports_decl("-webkit-backdrop-filter", "blur(4px)"))
+            .and(!supports_selector(":has(a)"));
+        assert_eq!(
+            condition.render(),
+            "@supports ((backdrop-filter: blur(4px)) or (-webkit-backdrop-filter: blur(4px))) and (not selector(:has(a)))"
+        );
+
+        use_css_registry();
+        let mut grid: Node<()> = div![];
+        grid.style(
+            s().display_flex()
+                .hover()
+                .supports(CssDisplay::Grid)
+                .media(media().min_width(px(600))),
+        );
+
+        let css = rendered_css();
+        assert!(css.contains("@supports (display: grid){\n@media (min-width: 600px){\n.sst-class.seedstyle-"));
+        assert!(css.contains(":hover{\ndisplay: flex;"));
+    }
+
+    #[test]
+    fn renders_cascade_layers() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        set_layer_order(&["reset", "vendor", "components"]);
+        layered_css("vendor", ".btn{color: red;}");
+        GlobalStyle::new()
+            .layer(CascadeLayer::Reset)
+            .style("body", s().margin(px(0)))
+            .activate_init_styles();
+        let mut button: Node<()> = button![];
+        button.style(s().color("blue").layer(CascadeLayer::Components));
+
+        let css = rendered_css();
+        assert!(css.starts_with("@layer reset, vendor, components;"));
+        assert_eq!(css.matches("@layer reset, vendor, components;").count(), 1);
+        assert!(css.contains("@layer vendor{\n.btn{color: red;}\n}"));
+        assert!(css.contains("@layer reset{\n.seed-init-style body{\nmargin: 0px;"));
+        assert!(css.contains("@layer components{\n.sst-class.seedstyle-"));
+
+        layered_css("vendor", ".btn{color: red;}");
+        collect_unused_styles();
+        assert_eq!(rendered_css().matches("@layer vendor{").count(), 1);
+    }
+
+    #[test]
+    fn renders_important_and_commented_rules() {
+        use crate::*;
+
+        let style = s()
+            .color("red")
+            .important()
+            .comment("overrides the vendor button")
+            .margin(px(2));
+        assert_eq!(
+            style.render(),
+            "/* overrides the vendor button */\ncolor: red !important;\nmargin: 2px;\n"
+        );
+        assert_eq!(
+            s().padding(px(1)).all_important().render(),
+            "padding: 1px !important;\n"
+        );
+        let mut rule = Rule::new(Box::new(CssColor::from("blue")));
+        rule.flags_mut().important = true;
+        assert_eq!(rule.value_only().trim(), "blue !important");
+    }
+
+    #[test]
+    fn flags_every_breakpoint_of_a_responsive_rule() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        app_themes().update(|themes| themes.push(Theme::new("bps").breakpoint_scale([600])));
+        let mut node: Node<()> = div![];
+        node.style(s().width(&[px(1), px(2)]).important().comment("fluid"));
+
+        let css = rendered_css();
+        assert!(css.contains("width: 1px !important;"));
+        assert!(css.contains("width: 2px !important;"));
+        assert_eq!(css.matches("/* fluid */").count(), 2);
+    }
+
+    #[test]
+    fn stacks_pseudo_classes() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        let mut button: Node<()> = button![];
+        button.style(
+            s().color("red")
+                .before()
+                .hover()
+                .not(":disabled")
+                .hover()
+                .after(),
+        );
+
+        let css = rendered_css();
+        assert!(css.contains(":hover:not(:disabled)::after{\ncolor: red;"));
+    }
+
+    #[test]
+    fn marks_elements_with_each_of_their_styles() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        let card = s().name("card").padding(px(8));
+        let active = s().color("red");
+        let mut node: Node<()> = div![];
+        node.style(card.clone());
+        node.style(active.clone());
+        let mut list: Node<()> = ul![];
+        list.style(s().has(&active));
+
+        let classes = match &node {
+            Node::Element(el) => match el.attrs.vals.get(&At::Class) {
+                Some(AtValue::Some(classes)) => classes.clone(),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+        assert!(classes.split(' ').any(|class| class == card.class_name()));
+        assert!(classes.split(' ').any(|class| class == active.class_name()));
+        assert!(rendered_css().contains(&format!(":has({}){{", active.class_selector())));
+    }
+
+    #[test]
+    fn renders_modern_pseudos() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        let selected = s().name("selected").font_weight("bold");
+        let mut item: Node<()> = li![];
+        item.style(
+            s().color("red")
+                .focus_visible()
+                .has(&selected)
+                .where_(".menu > li")
+                .placeholder(),
+        );
+
+        let css = rendered_css();
+        assert!(css.contains(&format!(
+            ":focus-visible:has({}):where(.menu > li)::placeholder{{",
+            selected.class_selector()
+        )));
+        assert!(selected.class_selector().starts_with(".seedmark-selected-"));
+    }
+
+    #[test]
+    fn renders_nth_formulas() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        assert_eq!(Nth::new(-1, 3).to_string(), "-n+3");
+        assert_eq!(Nth::new(3, -2).to_string(), "3n-2");
+        assert_eq!(Nth::even().of(".visible").to_string(), "even of .visible");
+
+        use_css_registry();
+        let mut row: Node<()> = tr![];
+        row.style(s().background_color("#eee").nth_child(Nth::odd()));
+        let mut cell: Node<()> = td![];
+        cell.style(s().color("red").nth_last_child(2));
+
+        let css = rendered_css();
+        assert!(css.contains(":nth-child(odd){"));
+        assert!(css.contains(":nth-last-child(2){"));
+    }
+
+    #[test]
+    fn renders_typed_selectors() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        assert_eq!(
+            element("ul")
+                .child(element("li").pseudo(Pseudo::FirstChild))
+                .child(this())
+                .or(attr_eq("type", "text"))
+                .render_for(".x"),
+            "ul > li:first-child > .x, .x [type=\"text\"]"
+        );
+
+        use_css_registry();
+        let card = s().name("card").padding(px(8));
+        let mut icon: Node<()> = span![];
+        icon.style(
+            s().color("gray")
+                .target(Selector::from(&card).pseudo(Pseudo::Hover).descendant(this())),
+        );
+
+        let css = rendered_css();
+        assert!(css.contains(&format!(
+            "{}:hover .sst-class.seedstyle-",
+            card.class_selector()
+        )));
+    }
+
+    #[test]
+    fn renders_this_in_combinators_and_pseudos() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        app_themes().update(|themes| themes.push(Theme::new("bps").breakpoint_scale([600])));
+        let mut item: Node<()> = li![];
+        item.style(
+            s().color("red")
+                .child_of(element("ul").class("open"))
+                .has(this().child(element("img"))),
+        );
+        let mut link: Node<()> = a![];
+        link.style(
+            s().color("blue")
+                .hover()
+                .target(element("nav").descendant(this()))
+                .width(&[px(1), px(2)]),
+        );
+
+        let css = rendered_css();
+        assert!(!css.contains('&'));
+        assert!(css.contains("ul.open > .sst-class.seedstyle-"));
+        assert!(css.contains(":has(> img){"));
+        assert_eq!(css.matches("nav .sst-class.seedstyle-").count(), 3);
+        assert_eq!(css.matches(":hover{").count(), 3);
+    }
+
+    #[test]
+    fn renders_nested_rules() {
+        use crate::*;
+        use seed::{prelude::*, *};
+
+        use_css_registry();
+        let mut list: Node<()> = ul![];
+        list.style(
+            s().margin(px(0))
+                .nest("& > li", s().padding(px(4)).hover())
+                .child(
+                    "svg",
+                    s().width(px(16))
+                        .media(media().max_width(px(599)))
+                        .nest("& path", s().color("red")),
+                ),
+        );
+
+        let css = rendered_css();
+        let class = css
+            .split(".sst-class.")
+            .nth(1)
+            .and_then(|rest| rest.split('{').next())
+            .unwrap()
+            .to_string();
+        assert!(css.contains(&format!(".sst-class.{} > li:hover{{\npadding: 4px;", class)));
+        assert!(css.contains(&format!(
+            "@media (max-width: 599px){{\n.sst-class.{} > svg{{\nwidth: 16px;",
+            class
+        )));
+        assert!(css.contains(&format!(
+            "@media (max-width: 599px){{\n.sst-class.{} > svg path{{\ncolor: red;",
+            class
+        )));
+    }
+
+    #[test]
+    fn separates_styles_built_at_one_call_site() {
+        use crate::*;
+
+        let classes = |build: &dyn Fn(usize) -> Style| {
+            let mut classes = (0..2).map(|idx| build(idx).class_name()).collect::<Vec<_>>();
+            classes.dedup();
+            classes.len()
+        };
+        let queries = [media().max_width(px(599)), media().min_width(px(600))];
+        assert_eq!(classes(&|idx| s().color("red").media(&queries[idx])), 2);
+        assert_eq!(classes(&|idx| s().color("red").nth_child(idx + 1)), 2);
+        assert_eq!(classes(&|idx| s().color("red").target(attr(&idx.to_string()))), 2);
+    }
 }
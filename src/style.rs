@@ -3,12 +3,10 @@ use harsh::{Harsh, HarshBuilder};
 use seed::{prelude::*, *};
 use seed_hooks::*;
 
-use std::cell::{Cell, RefCell};
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::panic::Location;
-use wasm_bindgen::JsCast;
 
 pub mod css_values;
 pub use css_values::*;
@@ -17,10 +15,42 @@ pub mod row_col_layout;
 pub use row_col_layout::*;
 
 pub mod measures;
+use measures::px;
 
 pub mod theme;
 use theme::*;
 
+pub mod theme_validation;
+use theme_validation::*;
+
+pub mod color_scheme;
+
+pub mod breakpoints;
+
+pub mod media_query;
+pub use media_query::*;
+
+pub mod supports;
+pub use supports::*;
+
+pub mod layers;
+pub use layers::*;
+
+pub mod nth;
+pub use nth::*;
+
+pub mod selector;
+pub use selector::*;
+
+#[cfg(feature = "theme-files")]
+pub mod theme_files;
+
+#[cfg(feature = "theme-files")]
+pub mod design_tokens;
+
+#[cfg(feature = "theme-files")]
+pub mod theme_export;
+
 pub mod composition;
 
 pub mod layout;
@@ -30,6 +60,9 @@ pub mod helpers;
 pub mod presets;
 use seed_style_macros::CssPseudoMacro;
 
+pub mod stylesheet;
+use stylesheet::*;
+
 use presets::*;
 
 mod from_traits;
@@ -41,16 +74,34 @@ pub fn s() -> Style {
 }
 
 thread_local! {
-    pub static GLOBAL_STYLES_COUNT: Cell<u32> = Cell::new(0);
-
-    pub static STYLES_USED : RefCell<HashSet<u64>> = RefCell::new(HashSet::<u64>::new());
     pub static HASH_IDS_GENERATOR: RefCell<Harsh> = RefCell::new(HarshBuilder::new().init().unwrap());
 }
 
+// `log!` calls into the browser and panics anywhere else, so off wasm, e.g. when rendering on
+// the server or in `cargo test`, warnings go to stderr instead.
+pub(crate) fn log_warning(message: String) {
+    if cfg!(target_arch = "wasm32") {
+        log!(message);
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
 fn short_uniq_id(id: u64) -> String {
     HASH_IDS_GENERATOR.with(|h| h.borrow().encode(&[id]).unwrap())
 }
 
+// The style hash a `seedstyle-` class was generated from.
+pub(crate) fn generated_class_hash(class_name: &str) -> Option<u64> {
+    if !class_name.starts_with("seedstyle-") {
+        return None;
+    }
+    let harsh_code = class_name.split('-').last()?;
+    HASH_IDS_GENERATOR
+        .with(|h| h.borrow().decode(harsh_code))
+        .and_then(|hash| hash.first().cloned())
+}
+
 use objekt_clonable::*;
 #[clonable]
 pub trait CssValueTrait: std::fmt::Display + Clone + Sync + Send + std::fmt::Debug {
@@ -64,33 +115,96 @@ pub trait CssValueTrait: std::fmt::Display + Clone + Sync + Send + std::fmt::Deb
 #[derive(Clone, Debug)]
 pub struct Rule {
     pub value: Box<dyn CssValueTrait>,
+    flags: RuleFlags,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleFlags {
+    pub important: bool,
+    // rendered as a css comment above the declaration
+    pub comment: Option<String>,
 }
 
 impl Rule {
+    pub fn new(value: Box<dyn CssValueTrait>) -> Rule {
+        Rule {
+            value,
+            flags: RuleFlags::default(),
+        }
+    }
+
+    pub fn flags(&self) -> &RuleFlags {
+        &self.flags
+    }
+
+    pub fn flags_mut(&mut self) -> &mut RuleFlags {
+        &mut self.flags
+    }
+
+    // `color: red;` -> `color: red !important;`
+    fn declaration(&self, declaration: String) -> String {
+        if self.flags.important {
+            format!(
+                "{} !important;",
+                declaration.trim_end().trim_end_matches(';')
+            )
+        } else {
+            declaration
+        }
+    }
+
+    pub fn value_only(&self) -> String {
+        if self.flags.important {
+            format!("{} !important", self.value.value_only())
+        } else {
+            self.value.value_only()
+        }
+    }
+
     fn render(&self) -> String {
+        let mut rendered = String::new();
+        if let Some(comment) = &self.flags.comment {
+            // a `*/` inside the text would end the comment early
+            rendered.push_str(&format!("/* {} */\n", comment.replace("*/", "* /")));
+        }
+        rendered.push_str(&format!("{}\n", self.declaration(self.value.to_string())));
         if let Some(prefixes) = &self.value.prefixes() {
-            let mut rendered = format!("{}\n", self.value);
             for prefix in prefixes {
-                rendered.push_str(&format!("{}{}\n", prefix, self.value));
+                rendered.push_str(&format!(
+                    "{}\n",
+                    self.declaration(format!("{}{}", prefix, self.value))
+                ));
             }
-            rendered
-        } else {
-            format!("{}\n", self.value)
         }
+        rendered
     }
 }
 
+// Where a rule added by the last property call is stored.
+#[derive(Clone, Debug)]
+enum AddedRule {
+    Rule(usize),
+    Media(String, usize),
+}
+
 #[derive(Clone, Debug)]
 pub struct Style {
     media_rules: HashMap<String, Vec<Rule>>,
     rules: Vec<Rule>,
+    // the rules `important`, `comment` and `annotate` apply to
+    last_added: Vec<AddedRule>,
     updated_at: Vec<String>,
-    pseudo: Pseudo,
+    pseudo: PseudoSelectors,
     media: Option<String>,
+    container: Option<String>,
+    supports: Option<SupportsCondition>,
+    layer: Option<CascadeLayer>,
     name: String,
     keyframes: Keyframes,
     combinator: Option<Combinator>,
     pre_combinators: Vec<Combinator>,
+    target: Option<Selector>,
+    nested: Vec<(Selector, Style)>,
 }
 
 impl Default for Style {
@@ -98,14 +212,20 @@ impl Default for Style {
     fn default() -> Self {
         Style {
             media_rules: HashMap::new(),
-            pseudo: Pseudo::None,
+            pseudo: PseudoSelectors::default(),
             updated_at: vec![format!("{}", Location::caller())],
             rules: vec![],
+            last_added: vec![],
             name: "".to_string(),
             media: None,
+            container: None,
+            supports: None,
+            layer: None,
             keyframes: Keyframes::default(),
             combinator: None,
             pre_combinators: vec![],
+            target: None,
+            nested: vec![],
         }
     }
 }
@@ -160,26 +280,26 @@ where
     fn update_style(self, style: &mut Style) {
         if let Some(bp_scale) = with_themes(ReturnBpScale) {
             let mut old_style = None;
+            let mut media_rules: Vec<(String, Box<dyn CssValueTrait>)> = vec![];
 
             for (style_idx, bp) in bp_scale.iter().enumerate() {
                 if let Some(item) = self.get(style_idx) {
                     let specific_value: P = item.clone().into();
 
-                    let rules = style.media_rules.entry(bp.clone().0).or_insert(vec![]);
-                    rules.push(Rule {
-                        value: Box::new(specific_value.clone()),
-                    });
+                    media_rules.push((bp.render(), Box::new(specific_value.clone())));
 
                     old_style = Some(specific_value);
-                } else {
-                    let rules = style.media_rules.entry(bp.clone().0).or_insert(vec![]);
-                    rules.push(Rule {
-                        value: Box::new(old_style.clone().unwrap()),
-                    });
+                } else if let Some(old_style) = &old_style {
+                    media_rules.push((bp.render(), Box::new(old_style.clone())));
                 }
             }
+            style.add_media_rules(media_rules);
         } else {
-            panic!("No breakpoints have been defined!")
+            missing_breakpoints();
+            if let Some(item) = self.first() {
+                let specific_value: P = item.clone().into();
+                style.add_rule(Box::new(specific_value));
+            }
         };
     }
 }
@@ -419,10 +539,98 @@ impl Style {
     // }
 
     fn add_rule(&mut self, value: Box<dyn CssValueTrait>) {
-        self.rules.push(Rule {
-            // property,
-            value,
-        });
+        self.last_added = vec![AddedRule::Rule(self.rules.len())];
+        self.rules.push(Rule::new(value));
+    }
+
+    // the rules of one responsive property, keyed by their media query
+    fn add_media_rules(&mut self, media_rules: Vec<(String, Box<dyn CssValueTrait>)>) {
+        self.last_added = vec![];
+        for (media, value) in media_rules {
+            let rules = self.media_rules.entry(media.clone()).or_insert(vec![]);
+            self.last_added.push(AddedRule::Media(media, rules.len()));
+            rules.push(Rule::new(value));
+        }
+    }
+
+    // used by the `PseudoTrait` methods, pseudo-classes stack up in order
+    fn add_pseudo(&mut self, pseudo: Pseudo) {
+        self.pseudo.add(pseudo);
+    }
+
+    // A marker class every element with this style gets, also when several styles share one
+    // generated class. Used to refer to a style from other selectors, e.g. `.has(&style)`.
+    pub fn class_name(&self) -> String {
+        self.marker_class(hash_64(&self.hash_key(), &self.updated_at))
+    }
+
+    // `class_name()` for an already computed variant hash.
+    fn marker_class(&self, variant_hash: u64) -> String {
+        format!("seedmark-{}-{}", self.name, short_uniq_id(variant_hash))
+    }
+
+    pub fn class_selector(&self) -> String {
+        format!(".{}", self.class_name())
+    }
+
+    // Updates the rules added by the last property call, all of its breakpoints for a responsive one.
+    fn update_last_rule_flags<F: Fn(&mut RuleFlags)>(&mut self, modifier: &str, update: F) {
+        if self.last_added.is_empty() {
+            log_warning(format!(
+                "seed_style warning: `{}` has no declaration to apply to, add it after one",
+                modifier
+            ));
+        }
+        for added in &self.last_added {
+            let rule = match added {
+                AddedRule::Rule(idx) => self.rules.get_mut(*idx),
+                AddedRule::Media(media, idx) => self
+                    .media_rules
+                    .get_mut(media)
+                    .and_then(|rules| rules.get_mut(*idx)),
+            };
+            if let Some(rule) = rule {
+                update(&mut rule.flags);
+            }
+        }
+    }
+
+    // Marks the last declaration `!important`, e.g. `s().color("red").important()`.
+    #[track_caller]
+    pub fn important(mut self) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.update_last_rule_flags("important", |flags| flags.important = true);
+        self
+    }
+
+    // Marks every declaration added so far `!important`, including responsive ones.
+    #[track_caller]
+    pub fn all_important(mut self) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        let media_rules = self.media_rules.values_mut().flatten();
+        for rule in self.rules.iter_mut().chain(media_rules) {
+            rule.flags.important = true;
+        }
+        self
+    }
+
+    // Adds a css comment above the last declaration.
+    #[track_caller]
+    pub fn comment(mut self, comment: &str) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.update_last_rule_flags("comment", |flags| flags.comment = Some(comment.to_string()));
+        self
+    }
+
+    // Annotates the last declaration with the source location of this call,
+    // useful to find where a rule seen in the devtools was written.
+    #[track_caller]
+    pub fn annotate(mut self) -> Style {
+        let location = format!("{}", Location::caller());
+        self.updated_at.push(location.clone());
+        let comment = format!("defined at {}", location);
+        self.update_last_rule_flags("annotate", |flags| flags.comment = Some(comment.clone()));
+        self
     }
 
     // The below macro creates psuedo matods like this one...
@@ -476,87 +684,203 @@ impl Style {
         self
     }
 
+    // declares a css custom property, e.g. s().var("--brand", "#f00")
+    #[track_caller]
+    pub fn var<V: CustomPropertyValue>(mut self, name: &str, val: V) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.add_rule(Box::new(CssCustomProperty {
+            name: custom_property_name(name),
+            value: val.custom_property_value(),
+        }));
+        self
+    }
+
+    #[track_caller]
+    pub fn media<Q: Into<MediaQuery>>(mut self, query: Q) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.media = Some(query.into().render());
+        self
+    }
+
+    // Makes elements with this style query containers, e.g. `.container("panel", CssContainerType::InlineSize)`.
     #[track_caller]
-    pub fn media(mut self, val: &str) -> Style {
+    pub fn container<T: Into<CssContainerType>>(mut self, name: &str, container_type: T) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.media = Some(val.to_string());
+        self.add_rule(Box::new(CssContainerName::StringValue(name.to_string())));
+        self.add_rule(Box::new(container_type.into()));
         self
     }
 
+    // Applies this style inside an `@container` query, e.g.
+    // `.container_query("panel", media().min_width(px(400)))`. An empty name queries the nearest container.
     #[track_caller]
-    pub fn follows(mut self, val: &str) -> Style {
+    pub fn container_query<Q: Into<MediaQuery>>(mut self, name: &str, condition: Q) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        let condition = condition.into().container_condition();
+        self.container = Some(if name.is_empty() {
+            format!("@container {}", condition)
+        } else {
+            format!("@container {} {}", name, condition)
+        });
+        self
+    }
+
+    // Applies this style only where the browser supports a feature, e.g.
+    // `s().display_flex().supports(CssDisplay::Grid)` for a grid layout with a flex fallback.
+    // Calling it again requires both conditions.
+    #[track_caller]
+    pub fn supports<C: Into<SupportsCondition>>(mut self, condition: C) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        let condition = condition.into();
+        self.supports = Some(match self.supports.take() {
+            Some(existing) => existing.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    // Puts this style in a cascade layer, e.g. `.layer(CascadeLayer::Utilities)`.
+    #[track_caller]
+    pub fn layer<L: Into<CascadeLayer>>(mut self, layer: L) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.layer = Some(layer.into());
+        self
+    }
+
+    // The at rules this style is nested in, outermost first.
+    fn at_rule_preludes(&self) -> Vec<String> {
+        self.layer
+            .iter()
+            .map(|layer| format!("@layer {}", layer))
+            .chain(self.supports.iter().map(|condition| condition.render()))
+            .chain(self.media.iter().cloned())
+            .chain(self.container.iter().cloned())
+            .collect()
+    }
+
+    // Styles the element matched by `selector`, see `Selector`. It takes the place of the
+    // combinator methods below, which can only express one step.
+    #[track_caller]
+    pub fn target<S: Into<Selector>>(mut self, selector: S) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.target = Some(selector.into());
+        self
+    }
+
+    // A rule nested under this style's class, `&` stands for the styled element, e.g.
+    // `s().nest("& > li", s().padding(px(4)))`. Nested styles can have pseudos, media queries
+    // and nested rules of their own.
+    #[track_caller]
+    pub fn nest<S: Into<Selector>>(mut self, selector: S, style: Style) -> Style {
+        self.updated_at.push(format!("{}", Location::caller()));
+        self.nested.push((selector.into(), style));
+        self
+    }
+
+    // `s().child("svg", s().width(px(16)))` -> `& > svg`
+    #[track_caller]
+    pub fn child<S: Into<Selector>>(self, selector: S, style: Style) -> Style {
+        self.nest(this().child(selector), style)
+    }
+
+    // Everything the css of a generated class is derived from: the declarations, responsive ones
+    // included, the selector, the at rules and the nested rules.
+    fn hash_key(&self) -> String {
+        let mut media_rules = self
+            .media_rules
+            .iter()
+            .map(|(media, rules)| {
+                let css = rules.iter().map(|rule| rule.render()).collect::<String>();
+                format!("{}{{{}}}", media, css)
+            })
+            .collect::<Vec<_>>();
+        media_rules.sort();
+        let mut key = format!(
+            "{}{}{}{:?}{:?}",
+            self.render(),
+            media_rules.concat(),
+            self.pseudo.render(),
+            self.at_rule_preludes(),
+            self.target
+        );
+        for (selector, style) in &self.nested {
+            key.push_str(&format!(
+                "{}{}{:?}{{{}}}",
+                selector,
+                style.pseudo.render(),
+                style.at_rule_preludes(),
+                style.hash_key()
+            ));
+        }
+        key
+    }
+
+    #[track_caller]
+    pub fn follows<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
         self.combinator = Some(Combinator::Post(PostCombinator::AdjacentSiblingFollows(
-            val.to_string(),
+            val.into(),
         )));
         self
     }
 
     #[track_caller]
-    pub fn sibling_of(mut self, val: &str) -> Style {
+    pub fn sibling_of<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.combinator = Some(Combinator::Post(PostCombinator::GeneralSibingFollows(
-            val.to_string(),
-        )));
+        self.combinator = Some(Combinator::Post(PostCombinator::GeneralSibingFollows(val.into())));
         self
     }
 
     #[track_caller]
-    pub fn child_of(mut self, val: &str) -> Style {
+    pub fn child_of<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.combinator = Some(Combinator::Post(PostCombinator::IsDirectChildOf(
-            val.to_string(),
-        )));
+        self.combinator = Some(Combinator::Post(PostCombinator::IsDirectChildOf(val.into())));
         self
     }
 
     #[track_caller]
-    pub fn descendant_of(mut self, val: &str) -> Style {
+    pub fn descendant_of<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.combinator = Some(Combinator::Post(PostCombinator::IsChildOf(val.to_string())));
+        self.combinator = Some(Combinator::Post(PostCombinator::IsChildOf(val.into())));
         self
     }
 
     #[track_caller]
-    pub fn style_following(mut self, val: &str) -> Style {
+    pub fn style_following<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
         self.pre_combinators = vec![Combinator::Pre(PreCombinator::AdjacentSiblingPreceeds(
-            val.to_string(),
+            val.into(),
         ))];
         self
     }
 
     #[track_caller]
-    pub fn style_sibling(mut self, val: &str) -> Style {
+    pub fn style_sibling<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
         self.pre_combinators = vec![Combinator::Pre(PreCombinator::GeneralSibingPreceeds(
-            val.to_string(),
+            val.into(),
         ))];
         self
     }
 
     #[track_caller]
-    pub fn style_child(mut self, val: &str) -> Style {
+    pub fn style_child<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.pre_combinators = vec![Combinator::Pre(PreCombinator::IsDirectParentOf(
-            val.to_string(),
-        ))];
+        self.pre_combinators = vec![Combinator::Pre(PreCombinator::IsDirectParentOf(val.into()))];
         self
     }
 
     #[track_caller]
-    pub fn style_other(mut self, val: &str) -> Style {
+    pub fn style_other<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.pre_combinators = vec![Combinator::Pre(PreCombinator::CombinatorForOther(
-            val.to_string(),
-        ))];
+        self.pre_combinators = vec![Combinator::Pre(PreCombinator::CombinatorForOther(val.into()))];
         self
     }
 
     #[track_caller]
-    pub fn style_descendant(mut self, val: &str) -> Style {
+    pub fn style_descendant<S: Into<Selector>>(mut self, val: S) -> Style {
         self.updated_at.push(format!("{}", Location::caller()));
-        self.pre_combinators = vec![Combinator::Pre(PreCombinator::IsParentOf(val.to_string()))];
+        self.pre_combinators = vec![Combinator::Pre(PreCombinator::IsParentOf(val.into()))];
         self
     }
 
@@ -567,7 +891,7 @@ impl Style {
         for sibling in siblings.iter() {
             self.pre_combinators
                 .push(Combinator::Pre(PreCombinator::GeneralSibingPreceeds(
-                    sibling.to_string(),
+                    Selector::from(*sibling),
                 )));
         }
         self
@@ -581,7 +905,7 @@ impl Style {
         for child in children.iter() {
             self.pre_combinators
                 .push(Combinator::Pre(PreCombinator::IsDirectParentOf(
-                    child.to_string(),
+                    Selector::from(*child),
                 )));
         }
 
@@ -595,7 +919,7 @@ impl Style {
         for descendant in descendants.iter() {
             self.pre_combinators
                 .push(Combinator::Pre(PreCombinator::IsParentOf(
-                    descendant.to_string(),
+                    Selector::from(*descendant),
                 )));
         }
         self
@@ -608,7 +932,7 @@ impl Style {
         let bp_pair = with_themes(ReturnBpTuple(bp));
 
         match bp_pair {
-            (_lower, Some(higher)) => self.media(&format!("@media (max-width: {}px)", higher - 1)),
+            (_lower, Some(higher)) => self.media(media().max_width(px(higher.saturating_sub(1)))),
             (_lower, None) => self.clone(),
         }
     }
@@ -620,40 +944,78 @@ impl Style {
         let bp_pair = with_themes(ReturnBpTuple(bp));
 
         match bp_pair {
-            (lower, Some(higher)) => self.media(&format!(
-                "@media (min-width:{}px) and (max-width: {}px)",
-                lower,
-                higher - 1
-            )),
-            (lower, None) => self.media(&format!("@media (min-width:{}px)", lower)),
+            (lower, Some(higher)) => self.media(
+                media()
+                    .min_width(px(lower))
+                    .max_width(px(higher.saturating_sub(1))),
+            ),
+            (lower, None) => self.media(media().min_width(px(lower))),
         }
     }
 
     pub fn only_and_above<T>(self, bp: T) -> Style
+    where
+        T: BreakpointTheme + 'static,
+    {
+        let (lower, _higher) = with_themes(ReturnBpTuple(bp));
+        self.media(media().min_width(px(lower)))
+    }
+
+    pub fn except<T>(self, bp: T) -> Style
     where
         T: BreakpointTheme + 'static,
     {
         let bp_pair = with_themes(ReturnBpTuple(bp));
 
+        let below = if bp_pair.0 > 0 {
+            media().max_width(px(bp_pair.0 - 1))
+        } else {
+            // nothing is below a breakpoint starting at 0
+            !media().all()
+        };
         match bp_pair {
-            (lower, Some(_higher)) => self.media(&format!("@media (min-width:{}px)", lower)),
-            (lower, None) => self.media(&format!("@media (min-width:{}px)", lower)),
+            (_lower, Some(higher)) => self.media(below.or(media().min_width(px(higher)))),
+            (_lower, None) => self.media(below),
         }
     }
 
-    pub fn except<T>(self, bp: T) -> Style
+    // Theme breakpoints applied to the width of the nearest query container instead of the viewport.
+    pub fn container_only<T>(self, bp: T) -> Style
+    where
+        T: BreakpointTheme + 'static,
+    {
+        let bp_pair = with_themes(ReturnBpTuple(bp));
+
+        match bp_pair {
+            (lower, Some(higher)) => self.container_query(
+                "",
+                media()
+                    .min_width(px(lower))
+                    .max_width(px(higher.saturating_sub(1))),
+            ),
+            (lower, None) => self.container_query("", media().min_width(px(lower))),
+        }
+    }
+
+    pub fn container_only_and_above<T>(self, bp: T) -> Style
+    where
+        T: BreakpointTheme + 'static,
+    {
+        let (lower, _higher) = with_themes(ReturnBpTuple(bp));
+        self.container_query("", media().min_width(px(lower)))
+    }
+
+    pub fn container_only_and_below<T>(self, bp: T) -> Style
     where
         T: BreakpointTheme + 'static,
     {
         let bp_pair = with_themes(ReturnBpTuple(bp));
 
         match bp_pair {
-            (lower, Some(higher)) => self.media(&format!(
-                "@media (max-width:{}px) or (min-width: {}px)",
-                lower - 1,
-                higher
-            )),
-            (lower, None) => self.media(&format!("@media (max-width:{}px)", lower - 1)),
+            (_lower, Some(higher)) => {
+                self.container_query("", media().max_width(px(higher.saturating_sub(1))))
+            }
+            (_lower, None) => self.clone(),
         }
     }
 
@@ -690,7 +1052,7 @@ pub trait UpdateCustomStyle {
 impl UpdateCustomStyle for Style {
     fn update_style(self, style: &mut Style) {
         for rule in &self.rules {
-            style.add_rule(rule.value.clone());
+            style.rules.push(rule.clone());
         }
 
         for (key, value) in &self.media_rules {
@@ -701,6 +1063,10 @@ impl UpdateCustomStyle for Style {
 
         style.media = self.media.clone();
 
+        style.container = self.container.clone();
+        style.supports = self.supports.clone();
+        style.layer = self.layer.clone();
+
         style.name = self.name.clone();
 
         style.keyframes = self.keyframes.clone();
@@ -708,6 +1074,10 @@ impl UpdateCustomStyle for Style {
         style.combinator = self.combinator.clone();
 
         style.pre_combinators = self.pre_combinators.clone();
+
+        style.target = self.target.clone();
+
+        style.nested = self.nested.clone();
     }
 }
 
@@ -721,7 +1091,8 @@ where
     where
         Th: StyleTheme + 'static,
     {
-        let theme_style = with_themes(ReturnSpecificStyleFromStyleTheme(self)).unwrap();
+        let theme_style = with_themes(ReturnSpecificStyleFromStyleTheme(self.clone()))
+            .unwrap_or_else(|| missing_theme_value::<Th, Style>(&self));
         theme_style.update_style(style);
     }
 }
@@ -753,43 +1124,58 @@ where
     {
         it.rev()
             .find_map(|theme| theme.get::<T, (u32, Option<u32>)>(self.0.clone()))
-            .unwrap()
+            .unwrap_or_else(|| missing_theme_value::<T, (u32, Option<u32>)>(&self.0))
     }
 }
 
-#[derive(Display, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub enum Combinator {
     Pre(PreCombinator),
     Post(PostCombinator),
 }
 
-#[derive(Display, Clone, Debug)]
+// `this()` inside a combinator selector stands for the styled element, as in `Style::target`.
+#[derive(Clone, Debug)]
 pub enum PostCombinator {
-    #[display(fmt = "{} + ", _0)]
-    AdjacentSiblingFollows(String),
-    #[display(fmt = "{} ~ ", _0)]
-    GeneralSibingFollows(String),
-    #[display(fmt = "{} > ", _0)]
-    IsDirectChildOf(String),
-    #[display(fmt = "{} ", _0)]
-    IsChildOf(String),
+    AdjacentSiblingFollows(Selector),
+    GeneralSibingFollows(Selector),
+    IsDirectChildOf(Selector),
+    IsChildOf(Selector),
 }
 
-#[derive(Display, Clone, Debug)]
+impl PostCombinator {
+    fn render_for(&self, this: &str) -> String {
+        match self {
+            PostCombinator::AdjacentSiblingFollows(val) => format!("{} + ", val.render_parts(this)),
+            PostCombinator::GeneralSibingFollows(val) => format!("{} ~ ", val.render_parts(this)),
+            PostCombinator::IsDirectChildOf(val) => format!("{} > ", val.render_parts(this)),
+            PostCombinator::IsChildOf(val) => format!("{} ", val.render_parts(this)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub enum PreCombinator {
-    #[display(fmt = " + {}", _0)]
-    AdjacentSiblingPreceeds(String),
-    #[display(fmt = " ~ {}", _0)]
-    GeneralSibingPreceeds(String),
-    #[display(fmt = " > {}", _0)]
-    IsDirectParentOf(String),
-    #[display(fmt = " {}", _0)]
-    IsParentOf(String),
-    #[display(fmt = "{}", _0)]
-    CombinatorForOther(String),
+    AdjacentSiblingPreceeds(Selector),
+    GeneralSibingPreceeds(Selector),
+    IsDirectParentOf(Selector),
+    IsParentOf(Selector),
+    CombinatorForOther(Selector),
+}
+
+impl PreCombinator {
+    fn render_for(&self, this: &str) -> String {
+        match self {
+            PreCombinator::AdjacentSiblingPreceeds(val) => format!(" + {}", val.render_parts(this)),
+            PreCombinator::GeneralSibingPreceeds(val) => format!(" ~ {}", val.render_parts(this)),
+            PreCombinator::IsDirectParentOf(val) => format!(" > {}", val.render_parts(this)),
+            PreCombinator::IsParentOf(val) => format!(" {}", val.render_parts(this)),
+            PreCombinator::CombinatorForOther(val) => val.render_parts(this),
+        }
+    }
 }
 
-#[derive(Clone, Debug, CssPseudoMacro)]
+#[derive(Clone, Debug, PartialEq, CssPseudoMacro)]
 pub enum Pseudo {
     None,
     Active,
@@ -807,11 +1193,12 @@ pub enum Pseudo {
     LastOfType,
     Link,
     Lang(String),
+    #[selector]
     Not(String),
-    NthChild(usize),
-    NthLastChild(usize),
-    NthLastOfType(usize),
-    NthOfType(usize),
+    NthChild(Nth),
+    NthLastChild(Nth),
+    NthLastOfType(Nth),
+    NthOfType(Nth),
     OnlyOfType,
     OnlyChild,
     Optional,
@@ -823,12 +1210,102 @@ pub enum Pseudo {
     Target,
     Valid,
     Visited,
+    FocusVisible,
+    FocusWithin,
+    PlaceholderShown,
+    Indeterminate,
+    #[pseudo_fn = "default_"]
+    Default,
+    #[selector]
+    Has(String),
+    #[selector]
+    Is(String),
+    #[selector]
+    #[pseudo_fn = "where_"]
+    Where(String),
     Before,
     After,
+    Placeholder,
+    Selection,
+    Marker,
+    FirstLine,
+    FirstLetter,
+    Backdrop,
+    FileSelectorButton,
+    #[pseudo_fn = "pseudo"]
     Custom(String),
 }
 
+// Arguments of the selector-taking pseudo-classes `:not()`, `:has()`, `:is()` and `:where()`.
+// A `&Style` stands for the class it generates, e.g. `s().has(&selected_style)`.
+pub trait PseudoSelector {
+    fn pseudo_selector(&self) -> String;
+}
+
+impl PseudoSelector for &str {
+    fn pseudo_selector(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl PseudoSelector for String {
+    fn pseudo_selector(&self) -> String {
+        self.clone()
+    }
+}
+
+impl PseudoSelector for &Style {
+    fn pseudo_selector(&self) -> String {
+        self.class_selector()
+    }
+}
+
+// The pseudo-classes of a style in the order they were added, followed by at most one
+// pseudo-element, e.g. `s().hover().not(":disabled").before()` -> `:hover:not(:disabled)::before`.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct PseudoSelectors {
+    classes: Vec<Pseudo>,
+    element: Option<Pseudo>,
+}
+
+impl PseudoSelectors {
+    // `Pseudo::None` clears them, a pseudo-element replaces the previous one.
+    fn add(&mut self, pseudo: Pseudo) {
+        if pseudo == Pseudo::None {
+            *self = PseudoSelectors::default();
+        } else if pseudo.is_element() {
+            self.element = Some(pseudo);
+        } else if !self.classes.contains(&pseudo) {
+            self.classes.push(pseudo);
+        }
+    }
+
+    fn render(&self) -> String {
+        self.classes
+            .iter()
+            .chain(self.element.iter())
+            .map(|pseudo| pseudo.render())
+            .collect()
+    }
+}
+
 impl Pseudo {
+    pub fn is_element(&self) -> bool {
+        match self {
+            Pseudo::Before
+            | Pseudo::After
+            | Pseudo::Placeholder
+            | Pseudo::Selection
+            | Pseudo::Marker
+            | Pseudo::FirstLine
+            | Pseudo::FirstLetter
+            | Pseudo::Backdrop
+            | Pseudo::FileSelectorButton => true,
+            Pseudo::Custom(val) => val.starts_with("::"),
+            _ => false,
+        }
+    }
+
     fn render(&self) -> String {
         match self {
             Pseudo::None => "".to_string(),
@@ -857,12 +1334,27 @@ impl Pseudo {
             Pseudo::Target => ":target".to_string(),
             Pseudo::Valid => ":valid".to_string(),
             Pseudo::Visited => ":visited".to_string(),
+            Pseudo::FocusVisible => ":focus-visible".to_string(),
+            Pseudo::FocusWithin => ":focus-within".to_string(),
+            Pseudo::PlaceholderShown => ":placeholder-shown".to_string(),
+            Pseudo::Indeterminate => ":indeterminate".to_string(),
+            Pseudo::Default => ":default".to_string(),
             Pseudo::Before => "::before".to_string(),
             Pseudo::After => "::after".to_string(),
+            Pseudo::Placeholder => "::placeholder".to_string(),
+            Pseudo::Selection => "::selection".to_string(),
+            Pseudo::Marker => "::marker".to_string(),
+            Pseudo::FirstLine => "::first-line".to_string(),
+            Pseudo::FirstLetter => "::first-letter".to_string(),
+            Pseudo::Backdrop => "::backdrop".to_string(),
+            Pseudo::FileSelectorButton => "::file-selector-button".to_string(),
+            Pseudo::Has(val) => format!(":has({})", val),
+            Pseudo::Is(val) => format!(":is({})", val),
+            Pseudo::Where(val) => format!(":where({})", val),
             Pseudo::Lang(val) => format!(":lang({})", val),
             Pseudo::Not(val) => format!(":not({})", val),
             Pseudo::NthChild(val) => format!(":nth-child({})", val),
-            Pseudo::NthLastChild(val) => format!(":nth-last-chid({})", val),
+            Pseudo::NthLastChild(val) => format!(":nth-last-child({})", val),
             Pseudo::NthLastOfType(val) => format!(":nth-last-of-type({})", val),
             Pseudo::NthOfType(val) => format!(":nth-of-type({})", val),
             Pseudo::Custom(val) => format!("{}", val),
@@ -899,26 +1391,19 @@ fn add_global_init_css_to_head(
     global_classname: &str,
     selector: &str,
 ) {
-    let head_elem = document().get_elements_by_tag_name("head").item(0).unwrap();
-
     let css = if !style.keyframes.frames.is_empty() {
         format!("{}    animation-name: anim-{};\n", css, short_hash)
     } else {
         css.to_string()
     };
 
+    let this = format!(".{}{}", global_classname, selector);
     let pre_combinators_str = style
         .pre_combinators
         .iter()
         .map(|c| {
             if let Combinator::Pre(c) = c {
-                format!(
-                    ".{}{}{}{}",
-                    global_classname,
-                    selector,
-                    c,
-                    style.pseudo.render()
-                )
+                format!("{}{}{}", this, c.render_for(&this), style.pseudo.render())
             } else {
                 String::new()
             }
@@ -926,67 +1411,35 @@ fn add_global_init_css_to_head(
         .collect::<Vec<String>>()
         .join(",");
 
-    let full_css = if style.pre_combinators.len() > 0 {
-        match &style.media {
-            Some(media) => format!("{}{{\n{}{{\n{}}}}}\n", media, pre_combinators_str, css),
-
-            None => format!("\n{}{{\n{}}}\n", pre_combinators_str, css),
-        }
+    let rule_selector = if let Some(target) = &style.target {
+        target.render_for(&format!("{}{}", this, style.pseudo.render()))
+    } else if style.pre_combinators.len() > 0 {
+        pre_combinators_str
     } else {
-        match (&style.media, &style.combinator) {
-            (Some(media), Some(Combinator::Pre(c))) => format!(
-                "{}{{\n.{}{}{}{}{{\n{}}}}}\n",
-                media,
-                global_classname,
-                selector,
-                c,
-                style.pseudo.render(),
-                css
-            ),
-            (Some(media), Some(Combinator::Post(c))) => format!(
-                "{}{{\n.{}{}{}{}{{\n{}}}}}\n",
-                media,
-                global_classname,
-                c,
-                selector,
-                style.pseudo.render(),
-                css
-            ),
-            (Some(media), None) => format!(
-                "{}{{\n.{}{}{}{{\n{}}}}}\n",
-                media,
-                global_classname,
-                selector,
-                style.pseudo.render(),
-                css
-            ),
-            (None, Some(Combinator::Pre(c))) => format!(
-                "\n.{}{}{}{}{{\n{}}}\n",
-                global_classname,
-                selector,
-                c,
-                style.pseudo.render(),
-                css
-            ),
-            (None, Some(Combinator::Post(c))) => format!(
-                "\n.{}{}{}{}{{\n{}}}\n",
-                global_classname,
-                c,
-                selector,
-                style.pseudo.render(),
-                css
-            ),
-
-            (None, None) => format!(
-                "\n.{}{}{}{{\n{}}}\n",
+        match &style.combinator {
+            Some(Combinator::Pre(c)) => {
+                format!("{}{}{}", this, c.render_for(&this), style.pseudo.render())
+            }
+            Some(Combinator::Post(c)) => format!(
+                ".{}{}{}{}",
                 global_classname,
+                c.render_for(&this),
                 selector,
-                style.pseudo.render(),
-                css
+                style.pseudo.render()
             ),
+            None => format!("{}{}", this, style.pseudo.render()),
         }
     };
 
+    if style.layer.is_some() {
+        ensure_layer_order();
+    }
+
+    let full_css = wrap_in_at_rules(
+        &style.at_rule_preludes(),
+        format!("{}{{\n{}}}", rule_selector, css),
+    );
+
     // let full_css = match (&style.media, &style.combinator) {
     //     (Some(media), Some(Combinator::Pre(c))) => format!(
     //         "{}{{\n{}{}{}{{\n{}}}}}\n",
@@ -1029,92 +1482,34 @@ fn add_global_init_css_to_head(
     //     (None, None) => format!("\n{}{}{{\n{}}}\n", selector, style.pseudo.render(), css),
     // };
 
-    let css_stylesheet =
-        if let Some(style_elem) = head_elem.get_elements_by_tag_name("style").item(0) {
-            let style_elem = style_elem.dyn_into::<web_sys::HtmlStyleElement>().unwrap();
-            style_elem
-                .sheet()
-                .unwrap()
-                .dyn_into::<web_sys::CssStyleSheet>()
-                .unwrap()
-
-        // let existing_style_content = style_elem.inner_html();
-
-        // let new_style_content = format!("{}\n{}", existing_style_content, full_css);
-        // style_elem.set_inner_html(&new_style_content);
-        } else {
-            let style_elem = document()
-                .create_element("style")
-                .unwrap()
-                .dyn_into::<web_sys::HtmlStyleElement>()
-                .unwrap();
-
-            // style_elem.set_inner_html(&full_css);
-
-            let style_elem = style_elem.dyn_into::<web_sys::HtmlStyleElement>().unwrap();
-            let _ = head_elem.append_child(&style_elem);
-            style_elem
-                .sheet()
-                .unwrap()
-                .dyn_into::<web_sys::CssStyleSheet>()
-                .unwrap()
-        };
-
-    let rules_length = GLOBAL_STYLES_COUNT.with(|count| count.get());
-
     // log!(full_css);
-    let res = css_stylesheet.insert_rule_with_index(&full_css, rules_length);
-    if let Err(err) = res {
-        log!("error inserting style: ", err, full_css);
-    }
-
-    GLOBAL_STYLES_COUNT.with(|count| {
-        let mut c = count.get();
-        c += 1;
-        count.set(c);
-    });
+    insert_global_css_rule(&full_css);
 
     for (media_breakpoint, rule_vec) in &style.media_rules {
-        let mut media_string = String::new();
-        media_string.push_str(&format!(
-            "{}{{\n.{}{}{{\n",
-            media_breakpoint, global_classname, selector
-        ));
-
+        let mut rules = String::new();
         for rule in rule_vec {
-            media_string.push_str(&rule.render());
+            rules.push_str(&rule.render());
         }
+        let mut preludes = style.at_rule_preludes();
+        preludes.push(media_breakpoint.clone());
+        let media_string = wrap_in_at_rules(
+            &preludes,
+            format!(".{}{}{{\n{}}}", global_classname, selector, rules),
+        );
 
-        let rules_length = GLOBAL_STYLES_COUNT.with(|count| count.get());
-        let res = css_stylesheet.insert_rule_with_index(&media_string, rules_length);
-        if let Err(err) = res {
-            log!("error inserting style: ", err, media_string);
-        }
-        GLOBAL_STYLES_COUNT.with(|count| {
-            let mut c = count.get();
-            c += 1;
-            count.set(c);
-        });
+        insert_global_css_rule(&media_string);
+    }
+
+    for nested_rule in nested_css_rules(style, &rule_selector, &style.at_rule_preludes()) {
+        insert_global_css_rule(&nested_rule);
     }
 
     if !style.keyframes.frames.is_empty() {
-        let rules_length = GLOBAL_STYLES_COUNT.with(|count| count.get());
-        let res = css_stylesheet.insert_rule_with_index(
-            &format!(
-                "\n\n@keyframes anim-{}{{ \n  {}  \n}}\n",
-                short_hash,
-                style.keyframes.render()
-            ),
-            rules_length,
-        );
-        if let Err(err) = res {
-            log!("error inserting keyframes style: ", err);
-        }
-        GLOBAL_STYLES_COUNT.with(|count| {
-            let mut c = count.get();
-            c += 1;
-            count.set(c);
-        });
+        insert_global_css_rule(&format!(
+            "\n\n@keyframes anim-{}{{ \n  {}  \n}}\n",
+            short_hash,
+            style.keyframes.render()
+        ));
     }
 }
 
@@ -1293,31 +1688,15 @@ pub trait LocalUpdateEl<T> {
 impl<Ms> LocalUpdateEl<El<Ms>> for Style {
     fn update_el(self, el: &mut El<Ms>) {
         let rendered_css = self.render();
+        let hash_key = self.hash_key();
+        let variant_hash = hash_64(&hash_key, &self.updated_at);
+        C![self.marker_class(variant_hash)].update_el(el);
         let existing_style_hashes =
             if let Some(AtValue::Some(class_string)) = el.attrs.vals.get(&At::Class) {
                 let existing_style_hashes = class_string
                     .split(" ")
-                    .filter_map(|cls| {
-                        if cls.starts_with("seedstyle-") {
-                            let harsh_code = cls.split("-").last().unwrap();
-
-                            let hash = HASH_IDS_GENERATOR.with(|builder_refcell| {
-                                builder_refcell.borrow().decode(harsh_code).unwrap()
-                            });
-
-                            let hash = &hash.first().unwrap();
-                            let existing_style =
-                                STYLES_USED.with(|css_set_ref| css_set_ref.borrow().contains(hash));
-
-                            if existing_style {
-                                Some(**hash)
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
+                    .filter_map(generated_class_hash)
+                    .filter(|hash| style_already_created(hash))
                     .collect::<Vec<u64>>();
                 if !existing_style_hashes.is_empty() {
                     Some(existing_style_hashes)
@@ -1330,21 +1709,19 @@ impl<Ms> LocalUpdateEl<El<Ms>> for Style {
 
         if let Some(styles_in_elem) = existing_style_hashes {
             let mut s = DefaultHasher::new();
-            (styles_in_elem, self.render()).hash(&mut s);
+            (styles_in_elem, hash_key).hash(&mut s);
             let revised_variant_hash = s.finish();
 
-            let css_aleady_created = STYLES_USED
-                .with(|css_set_ref| css_set_ref.borrow().contains(&revised_variant_hash));
+            let css_aleady_created = style_already_created(&revised_variant_hash);
 
             if !css_aleady_created {
                 add_css_to_head_unchecked(&rendered_css, revised_variant_hash, &self, &self.name);
             }
+            mark_style_referenced(revised_variant_hash);
             let short_hash = format!("{}-{}", &self.name, short_uniq_id(revised_variant_hash));
             let class_name = format!("seedstyle-{}", short_hash);
             C![class_name].update_el(el);
         } else {
-            let variant_hash = hash_64(&rendered_css, &self.updated_at);
-
             let class_name = format!(
                 "seedstyle-{}",
                 add_css_to_head(&rendered_css, variant_hash, &self)
@@ -1362,12 +1739,12 @@ impl<Ms> LocalUpdateEl<El<Ms>> for &[Style] {
     fn update_el(self, el: &mut El<Ms>) {
         let vec_of_rendered_css = self.iter().map(|s| s.render()).collect::<Vec<String>>();
 
+        let hash_keys = self.iter().map(|s| s.hash_key()).collect::<Vec<String>>();
         let mut s = DefaultHasher::new();
-        vec_of_rendered_css.hash(&mut s);
+        hash_keys.hash(&mut s);
         let variant_hash = s.finish();
 
-        let css_aleady_created =
-            STYLES_USED.with(|css_set_ref| css_set_ref.borrow().contains(&variant_hash));
+        let css_aleady_created = style_already_created(&variant_hash);
 
         let mut name = "".to_string();
         for (_, style) in vec_of_rendered_css.iter().zip(self.iter()) {
@@ -1379,11 +1756,80 @@ impl<Ms> LocalUpdateEl<El<Ms>> for &[Style] {
                 add_css_to_head_unchecked(&rendered_css, variant_hash, style, &name);
             }
         }
+        mark_style_referenced(variant_hash);
         let short_hash = format!("{}-{}", name, short_uniq_id(variant_hash));
         let class_name = format!("seedstyle-{}", short_hash);
 
         C!["sst-class", class_name].update_el(el);
+        for (style, hash_key) in self.iter().zip(&hash_keys) {
+            C![style.marker_class(hash_64(hash_key, &style.updated_at))].update_el(el);
+        }
+    }
+}
+
+// A class generated at compile time by `static_style!`.
+// Its css lives in the extracted stylesheet so nothing is rendered or inserted at runtime.
+#[derive(Clone, Copy, Debug)]
+pub struct StaticStyle(pub &'static str);
+
+impl<Ms> LocalUpdateEl<El<Ms>> for StaticStyle {
+    fn update_el(self, el: &mut El<Ms>) {
+        C![self.0].update_el(el);
+    }
+}
+
+// Nests a rule in at rules such as `@media` and `@container`, the first prelude is the outermost.
+fn wrap_in_at_rules(preludes: &[String], rule: String) -> String {
+    if preludes.is_empty() {
+        return format!("\n{}\n", rule);
+    }
+    let wrapped = preludes
+        .iter()
+        .rev()
+        .fold(rule, |inner, prelude| format!("{}{{\n{}}}", prelude, inner));
+    format!("{}\n", wrapped)
+}
+
+// The rules of `style.nested`, `this` is the selector of the rule they are nested in.
+fn nested_css_rules(style: &Style, this: &str, preludes: &[String]) -> Vec<String> {
+    // `&` has to stand for the whole of a selector list
+    let this = if this.contains(',') {
+        format!(":is({})", this)
+    } else {
+        this.to_string()
+    };
+
+    let mut rules = vec![];
+    for (selector, nested) in &style.nested {
+        let selector = match nested.pseudo.render() {
+            pseudo if pseudo.is_empty() => selector.render_for(&this),
+            pseudo => selector
+                .clone()
+                .compound(Selector::Raw(pseudo))
+                .render_for(&this),
+        };
+        let mut nested_preludes = preludes.to_vec();
+        nested_preludes.extend(nested.at_rule_preludes());
+
+        let css = nested.render();
+        if !css.is_empty() {
+            rules.push(wrap_in_at_rules(
+                &nested_preludes,
+                format!("{}{{\n{}}}", selector, css),
+            ));
+        }
+        for (media_breakpoint, rule_vec) in &nested.media_rules {
+            let css = rule_vec.iter().map(|rule| rule.render()).collect::<String>();
+            let mut media_preludes = nested_preludes.clone();
+            media_preludes.push(media_breakpoint.clone());
+            rules.push(wrap_in_at_rules(
+                &media_preludes,
+                format!("{}{{\n{}}}", selector, css),
+            ));
+        }
+        rules.extend(nested_css_rules(nested, &selector, &nested_preludes));
     }
+    rules
 }
 
 fn hash_64<T: AsRef<str> + Hash>(css: &str, locations: &[T]) -> u64 {
@@ -1393,13 +1839,13 @@ fn hash_64<T: AsRef<str> + Hash>(css: &str, locations: &[T]) -> u64 {
 }
 
 fn add_css_to_head(css: &str, variant_hash: u64, style: &Style) -> String {
-    let css_aleady_created =
-        STYLES_USED.with(|css_set_ref| css_set_ref.borrow().contains(&variant_hash));
+    let css_aleady_created = style_already_created(&variant_hash);
     let short_hash = format!("{}-{}", style.name, short_uniq_id(variant_hash));
 
     if !css_aleady_created {
         add_css_to_head_unchecked(css, variant_hash, style, &style.name);
     }
+    mark_style_referenced(variant_hash);
 
     short_hash
 }
@@ -1409,25 +1855,19 @@ fn add_css_to_head_unchecked(css: &str, variant_hash: u64, style: &Style, name:
 
     // log!(short_hash);
 
-    let head_elem = document().get_elements_by_tag_name("head").item(0).unwrap();
-
     let css = if !style.keyframes.frames.is_empty() {
         format!("{}    animation-name: anim-{};\n", css, short_hash)
     } else {
         css.to_string()
     };
 
+    let this = format!(".sst-class.seedstyle-{}", short_hash);
     let pre_combinators_str = style
         .pre_combinators
         .iter()
         .map(|c| {
             if let Combinator::Pre(c) = c {
-                format!(
-                    ".sst-class.seedstyle-{}{}{}",
-                    short_hash,
-                    c,
-                    style.pseudo.render()
-                )
+                format!("{}{}{}", this, c.render_for(&this), style.pseudo.render())
             } else {
                 String::new()
             }
@@ -1435,155 +1875,73 @@ fn add_css_to_head_unchecked(css: &str, variant_hash: u64, style: &Style, name:
         .collect::<Vec<String>>()
         .join(",");
 
-    let full_css = if style.pre_combinators.len() > 0 {
-        match &style.media {
-            Some(media) => format!("{}{{\n{}{{\n{}}}}}\n", media, pre_combinators_str, css),
-
-            None => format!("\n{}{{\n{}}}\n", pre_combinators_str, css),
-        }
+    let rule_selector = if let Some(target) = &style.target {
+        target.render_for(&format!("{}{}", this, style.pseudo.render()))
+    } else if style.pre_combinators.len() > 0 {
+        pre_combinators_str.clone()
     } else {
-        match (&style.media, &style.combinator) {
-            (Some(media), Some(Combinator::Pre(c))) => format!(
-                "{}{{\n.sst-class.seedstyle-{}{}{}{{\n{}}}}}\n",
-                media,
-                short_hash,
-                c,
-                style.pseudo.render(),
-                css
-            ),
-            (Some(media), Some(Combinator::Post(c))) => format!(
-                "{}{{\n{}.sst-class.seedstyle-{}{}{{\n{}}}}}\n",
-                media,
-                c,
-                short_hash,
-                style.pseudo.render(),
-                css
-            ),
-            (Some(media), None) => format!(
-                "{}{{\n.sst-class.seedstyle-{}{}{{\n{}}}}}\n",
-                media,
-                short_hash,
-                style.pseudo.render(),
-                css
-            ),
-            (None, Some(Combinator::Pre(c))) => format!(
-                "\n.sst-class.seedstyle-{}{}{}{{\n{}}}\n",
-                short_hash,
-                c,
-                style.pseudo.render(),
-                css
-            ),
-            (None, Some(Combinator::Post(c))) => format!(
-                "\n{}.sst-class.seedstyle-{}{}{{\n{}}}\n",
-                c,
-                short_hash,
-                style.pseudo.render(),
-                css
-            ),
-
-            (None, None) => format!(
-                "\n.sst-class.seedstyle-{}{}{{\n{}}}\n",
-                short_hash,
-                style.pseudo.render(),
-                css
-            ),
+        match &style.combinator {
+            Some(Combinator::Pre(c)) => {
+                format!("{}{}{}", this, c.render_for(&this), style.pseudo.render())
+            }
+            Some(Combinator::Post(c)) => {
+                format!("{}{}{}", c.render_for(&this), this, style.pseudo.render())
+            }
+            None => format!("{}{}", this, style.pseudo.render()),
         }
     };
+
+    if style.layer.is_some() {
+        ensure_layer_order();
+    }
+
+    let full_css = wrap_in_at_rules(
+        &style.at_rule_preludes(),
+        format!("{}{{\n{}}}", rule_selector, css),
+    );
     // if !name.is_empty() {
     //   log!(full_css);
     // }
 
-    let css_stylesheet =
-        if let Some(style_elem) = head_elem.get_elements_by_tag_name("style").item(0) {
-            let style_elem = style_elem.dyn_into::<web_sys::HtmlStyleElement>().unwrap();
-            style_elem
-                .sheet()
-                .unwrap()
-                .dyn_into::<web_sys::CssStyleSheet>()
-                .unwrap()
-
-        // let existing_style_content = style_elem.inner_html();
-
-        // let new_style_content = format!("{}\n{}", existing_style_content, full_css);
-        // style_elem.set_inner_html(&new_style_content);
-        } else {
-            let style_elem = document()
-                .create_element("style")
-                .unwrap()
-                .dyn_into::<web_sys::HtmlStyleElement>()
-                .unwrap();
-
-            // style_elem.set_inner_html(&full_css);
-
-            let style_elem = style_elem.dyn_into::<web_sys::HtmlStyleElement>().unwrap();
-            let _ = head_elem.append_child(&style_elem);
-            style_elem
-                .sheet()
-                .unwrap()
-                .dyn_into::<web_sys::CssStyleSheet>()
-                .unwrap()
-        };
-
-    let rules_length = css_stylesheet.css_rules().unwrap().length();
     // log!(full_css);
-    let res = css_stylesheet.insert_rule_with_index(&full_css, rules_length);
-    if let Err(err) = res {
-        log!("error inserting style: ", err, full_css);
-    }
+    append_css_rule(&full_css, variant_hash);
 
     for (media_breakpoint, rule_vec) in &style.media_rules {
-        if style.pre_combinators.len() > 0 {
-            let mut media_string = String::new();
-            media_string.push_str(&format!(
-                "{}{{\n{}{{\n",
-                media_breakpoint, pre_combinators_str
-            ));
-
-            for rule in rule_vec {
-                media_string.push_str(&rule.render());
-            }
-            let rules_length = css_stylesheet.css_rules().unwrap().length();
-            // log!(media_string);
-            let res = css_stylesheet.insert_rule_with_index(&media_string, rules_length);
-            if let Err(err) = res {
-                log!("error inserting style: ", err, media_string);
-            }
+        let mut rules = String::new();
+        for rule in rule_vec {
+            rules.push_str(&rule.render());
+        }
+        let media_selector = if let Some(target) = &style.target {
+            target.render_for(&format!("{}{}", this, style.pseudo.render()))
+        } else if style.pre_combinators.len() > 0 {
+            pre_combinators_str.clone()
         } else {
-            let mut media_string = String::new();
-            media_string.push_str(&format!(
-                "{}{{\n.sst-class.seedstyle-{}{{\n",
-                media_breakpoint, short_hash
-            ));
+            format!(".sst-class.seedstyle-{}", short_hash)
+        };
+        let mut preludes = style.at_rule_preludes();
+        preludes.push(media_breakpoint.clone());
+        let media_string =
+            wrap_in_at_rules(&preludes, format!("{}{{\n{}}}", media_selector, rules));
+        // log!(media_string);
+        append_css_rule(&media_string, variant_hash);
+    }
 
-            for rule in rule_vec {
-                media_string.push_str(&rule.render());
-            }
-            let rules_length = css_stylesheet.css_rules().unwrap().length();
-            // log!(media_string);
-            let res = css_stylesheet.insert_rule_with_index(&media_string, rules_length);
-            if let Err(err) = res {
-                log!("error inserting style: ", err, media_string);
-            }
-        }
+    for nested_rule in nested_css_rules(style, &rule_selector, &style.at_rule_preludes()) {
+        append_css_rule(&nested_rule, variant_hash);
     }
 
     if !style.keyframes.frames.is_empty() {
-        let rules_length = css_stylesheet.css_rules().unwrap().length();
-        let res = css_stylesheet.insert_rule_with_index(
+        append_css_rule(
             &format!(
                 "\n\n@keyframes anim-{}{{ \n  {}  \n}}\n",
                 short_hash,
                 style.keyframes.render()
             ),
-            rules_length,
+            variant_hash,
         );
-
-        if let Err(err) = res {
-            log!("error inserting keyframes style: ", err);
-        }
     }
 
-    STYLES_USED.with(|css_set_ref| css_set_ref.borrow_mut().insert(variant_hash));
+    mark_style_created(variant_hash);
 
     short_hash
 }
@@ -1611,6 +1969,8 @@ impl<Ms> AddStyleToNode for Node<Ms> {
 #[derive(Default, Debug)]
 pub struct GlobalStyle {
     pub styles: Vec<(String, Style)>,
+    // layer of the styles that do not set their own
+    pub layer: Option<CascadeLayer>,
 }
 
 impl GlobalStyle {
@@ -1627,21 +1987,33 @@ impl GlobalStyle {
         self
     }
 
+    pub fn layer<L: Into<CascadeLayer>>(mut self, layer: L) -> GlobalStyle {
+        self.layer = Some(layer.into());
+        self
+    }
+
+    fn style_in_layer(&self, style: &Style) -> Style {
+        let mut style = style.clone();
+        if style.layer.is_none() {
+            style.layer = self.layer.clone();
+        }
+        style
+    }
+
     pub fn activate_init_styles(&self) {
         do_once(|| {
             let html_root_class = "seed-init-style ".to_string();
 
-            if let Some(html_root_elem) = document().get_elements_by_tag_name("html").item(0) {
-                if let Some(mut class_name) = html_root_elem.get_attribute("class") {
-                    class_name.push_str(" ");
-                    class_name.push_str(&html_root_class);
-                    let _ = html_root_elem.set_attribute("class", &class_name);
-                } else {
-                    let _ = html_root_elem.set_attribute("class", &format!(" {}", html_root_class));
-                }
+            if let Some(mut class_name) = current_html_root_class() {
+                class_name.push_str(" ");
+                class_name.push_str(&html_root_class);
+                set_html_root_class(&class_name);
+            } else {
+                set_html_root_class(&format!(" {}", html_root_class));
             }
 
             for (selector, style) in &self.styles {
+                let style = self.style_in_layer(style);
                 let rendered_css = style.render();
 
                 add_global_init_css_to_head(
@@ -1657,6 +2029,9 @@ impl GlobalStyle {
 
     pub fn activate_styles(&self) {
         let mut style_string = "global".to_string();
+        if let Some(layer) = &self.layer {
+            style_string.push_str(&layer.to_string());
+        }
 
         for (selector, style) in &self.styles {
             let rendered_css = style.render();
@@ -1672,36 +2047,33 @@ impl GlobalStyle {
 
         let html_root_class = format!("seed-global-style-{} ", short_hash);
 
-        if let Some(html_root_elem) = document().get_elements_by_tag_name("html").item(0) {
-            if let Some(mut class_name) = html_root_elem.get_attribute("class") {
-                if let Some(existing_seed_style_position) = class_name.find("seed-global-style-") {
-                    if let Some(terminating_position) =
-                        class_name[existing_seed_style_position..].find(" ")
-                    {
-                        let final_term_pos =
-                            existing_seed_style_position + terminating_position + 1;
-                        class_name.replace_range(
-                            existing_seed_style_position..final_term_pos,
-                            &html_root_class,
-                        )
-                    } else {
-                        class_name.replace_range(existing_seed_style_position.., &html_root_class)
-                    }
+        if let Some(mut class_name) = current_html_root_class() {
+            if let Some(existing_seed_style_position) = class_name.find("seed-global-style-") {
+                if let Some(terminating_position) =
+                    class_name[existing_seed_style_position..].find(" ")
+                {
+                    let final_term_pos = existing_seed_style_position + terminating_position + 1;
+                    class_name.replace_range(
+                        existing_seed_style_position..final_term_pos,
+                        &html_root_class,
+                    )
                 } else {
-                    class_name.push_str(" ");
-                    class_name.push_str(&html_root_class);
+                    class_name.replace_range(existing_seed_style_position.., &html_root_class)
                 }
-                let _ = html_root_elem.set_attribute("class", &format!("{}", &class_name));
             } else {
-                let _ = html_root_elem.set_attribute("class", &format!(" {}", &html_root_class));
+                class_name.push_str(" ");
+                class_name.push_str(&html_root_class);
             }
+            set_html_root_class(&class_name);
+        } else {
+            set_html_root_class(&format!(" {}", &html_root_class));
         }
 
-        let css_aleady_created =
-            STYLES_USED.with(|css_set_ref| css_set_ref.borrow().contains(&revised_variant_hash));
+        let css_aleady_created = style_already_created(&revised_variant_hash);
 
         if !css_aleady_created {
             for (selector, style) in &self.styles {
+                let style = self.style_in_layer(style);
                 let rendered_css = style.render();
 
                 add_global_init_css_to_head(
@@ -1711,11 +2083,7 @@ impl GlobalStyle {
                     &html_root_class,
                     &selector,
                 );
-                STYLES_USED.with(|css_set_ref| {
-                    css_set_ref
-                        .borrow_mut()
-                        .insert(revised_variant_hash.clone())
-                });
+                mark_style_created(revised_variant_hash);
             }
         }
     }
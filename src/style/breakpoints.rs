@@ -0,0 +1,184 @@
+use crate::style::measures::px;
+use crate::style::media_query::*;
+use crate::style::theme::*;
+use seed::{prelude::*, *};
+use seed_hooks::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+// Breakpoint service.
+//
+// Loading a theme with a breakpoint scale registers a `MediaQueryList` change listener for each
+// pair of the scale. The pair containing the viewport width is kept in the `current_breakpoint()`
+// atom, which is only updated when the viewport crosses a breakpoint, so views observing it
+// re-render exactly then.
+//
+// `only`, `only_and_above`, `except` etc. answer from the current breakpoint instead of querying
+// the browser on every render. Bounds that do not line up with the scale still fall back to
+// `match_media`.
+//
+// To receive a message instead:
+//
+// fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+//     notify_on_breakpoint_change(orders, Msg::BreakpointChanged);
+//     ..
+// }
+
+pub type BreakpointPair = (u32, Option<u32>);
+
+struct BreakpointListener {
+    pair: BreakpointPair,
+    query: web_sys::MediaQueryList,
+    on_change: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+thread_local! {
+    static LISTENERS: RefCell<Vec<BreakpointListener>> = RefCell::new(vec![]);
+    static CALLBACKS: RefCell<Vec<Rc<dyn Fn(BreakpointPair)>>> = RefCell::new(vec![]);
+}
+
+// The breakpoint scale pair containing the viewport width, `None` until a theme with a breakpoint scale is loaded.
+#[atom]
+pub fn current_breakpoint() -> Atom<Option<BreakpointPair>> {
+    None
+}
+
+pub(crate) fn pair_media_query(pair: BreakpointPair) -> MediaQuery {
+    match pair {
+        (lower, Some(higher)) => media().min_width(px(lower)).max_width(px(higher)),
+        (lower, None) => media().min_width(px(lower)),
+    }
+}
+
+// Asks the browser directly, prefer the breakpoint helpers for width queries.
+pub fn media_query_matches<Q: Into<MediaQuery>>(query: Q) -> bool {
+    let query = query.into().to_string();
+    web_sys::window()
+        .and_then(|window| window.match_media(&query).ok().flatten())
+        .map_or(false, |query| query.matches())
+}
+
+// Registers listeners for the breakpoint scale of the loaded themes, replacing any previous ones.
+pub fn start_breakpoint_service() {
+    let pairs = app_themes().observe_with(|themes| {
+        themes
+            .iter()
+            .rev()
+            .find(|theme| !theme.media_bp_pairs.is_empty())
+            .map(|theme| theme.media_bp_pairs.clone())
+            .unwrap_or_default()
+    });
+
+    let unchanged = LISTENERS.with(|listeners| {
+        listeners
+            .borrow()
+            .iter()
+            .map(|listener| listener.pair)
+            .eq(pairs.iter().cloned())
+    });
+    if unchanged {
+        return;
+    }
+
+    stop_breakpoint_service();
+
+    let window = if let Some(window) = web_sys::window() {
+        window
+    } else {
+        return;
+    };
+
+    for pair in pairs {
+        let query = if let Ok(Some(query)) = window.match_media(&pair_media_query(pair).to_string())
+        {
+            query
+        } else {
+            continue;
+        };
+        let on_change = Closure::wrap(Box::new(|_: web_sys::Event| update_current_breakpoint())
+            as Box<dyn FnMut(web_sys::Event)>);
+        if query
+            .add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())
+            .is_err()
+        {
+            log!("seed_style warning: could not listen for breakpoint changes");
+        }
+        LISTENERS.with(|listeners| {
+            listeners.borrow_mut().push(BreakpointListener {
+                pair,
+                query,
+                on_change,
+            })
+        });
+    }
+
+    update_current_breakpoint();
+}
+
+pub fn stop_breakpoint_service() {
+    let listeners = LISTENERS.with(|listeners| std::mem::take(&mut *listeners.borrow_mut()));
+    for listener in listeners {
+        let _ = listener.query.remove_event_listener_with_callback(
+            "change",
+            listener.on_change.as_ref().unchecked_ref(),
+        );
+    }
+}
+
+fn update_current_breakpoint() {
+    let matching = LISTENERS.with(|listeners| {
+        listeners
+            .borrow()
+            .iter()
+            .find(|listener| listener.query.matches())
+            .map(|listener| listener.pair)
+    });
+
+    if current_breakpoint().observe_with(|current| *current == matching) {
+        return;
+    }
+    current_breakpoint().update(|current| *current = matching);
+
+    if let Some(pair) = matching {
+        // cloned out of the cell so callbacks can register further callbacks
+        let callbacks = CALLBACKS.with(|callbacks| callbacks.borrow().clone());
+        for callback in callbacks {
+            callback(pair)
+        }
+    }
+}
+
+// Called with the new pair whenever the viewport crosses a breakpoint.
+pub fn on_breakpoint_change<F: Fn(BreakpointPair) + 'static>(callback: F) {
+    CALLBACKS.with(|callbacks| callbacks.borrow_mut().push(Rc::new(callback)))
+}
+
+pub fn notify_on_breakpoint_change<Ms, O>(orders: &mut O, to_msg: fn(BreakpointPair) -> Ms)
+where
+    Ms: 'static,
+    O: Orders<Ms>,
+{
+    let app = orders.clone_app();
+    let msg_mapper = orders.msg_mapper();
+    on_breakpoint_change(move |pair| app.update(msg_mapper(to_msg(pair))));
+}
+
+// viewport width >= width
+pub(crate) fn viewport_at_least(width: u32) -> bool {
+    match current_breakpoint().observe_with(|current| *current) {
+        Some((lower, _)) if width <= lower => true,
+        Some((_, Some(higher))) if width > higher => false,
+        _ => media_query_matches(media().min_width(px(width))),
+    }
+}
+
+// viewport width <= width
+pub(crate) fn viewport_at_most(width: u32) -> bool {
+    match current_breakpoint().observe_with(|current| *current) {
+        Some((_, Some(higher))) if higher <= width => true,
+        Some((lower, _)) if lower > width => false,
+        _ => media_query_matches(media().max_width(px(width))),
+    }
+}
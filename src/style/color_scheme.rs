@@ -0,0 +1,184 @@
+use crate::style::theme::*;
+use seed::{prelude::*, *};
+use seed_hooks::*;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+// Light and dark variants of a theme that follow the OS colour scheme.
+//
+// load_color_scheme_themes(light_theme, dark_theme);
+//
+// loads the variant matching `prefers-color-scheme` as an app theme and swaps it whenever the
+// OS setting changes. The user can override the OS setting:
+//
+// set_color_scheme_preference(ColorSchemePreference::Dark);
+//
+// The override is saved to localStorage and restored on the next visit. Both variants should set
+// the same aliases, usually by one extending the other with `Theme::extend`.
+//
+// Views observing `color_scheme()` re-render when the active variant changes, as do all views
+// using theme lookups. In css variable mode only the variable block is swapped.
+
+const PREFERS_DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+const STORAGE_KEY: &str = "seed_style_color_scheme";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSchemePreference {
+    Light,
+    Dark,
+    System,
+}
+
+impl ColorSchemePreference {
+    pub fn resolve(self, system_prefers_dark: bool) -> ColorScheme {
+        match self {
+            ColorSchemePreference::Light => ColorScheme::Light,
+            ColorSchemePreference::Dark => ColorScheme::Dark,
+            ColorSchemePreference::System if system_prefers_dark => ColorScheme::Dark,
+            ColorSchemePreference::System => ColorScheme::Light,
+        }
+    }
+
+    // as stored in localStorage
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColorSchemePreference::Light => "light",
+            ColorSchemePreference::Dark => "dark",
+            ColorSchemePreference::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ColorSchemePreference> {
+        match value {
+            "light" => Some(ColorSchemePreference::Light),
+            "dark" => Some(ColorSchemePreference::Dark),
+            "system" => Some(ColorSchemePreference::System),
+            _ => None,
+        }
+    }
+}
+
+struct ColorSchemeThemes {
+    light: fn() -> Theme,
+    dark: fn() -> Theme,
+    active: ColorScheme,
+    // name of the variant currently loaded as an app theme
+    active_name: String,
+}
+
+thread_local! {
+    static COLOR_SCHEME_THEMES: RefCell<Option<ColorSchemeThemes>> = RefCell::new(None);
+    static LISTENING: Cell<bool> = Cell::new(false);
+}
+
+#[atom]
+pub fn color_scheme_preference() -> Atom<ColorSchemePreference> {
+    stored_preference().unwrap_or(ColorSchemePreference::System)
+}
+
+#[atom]
+pub fn color_scheme() -> Atom<ColorScheme> {
+    stored_preference()
+        .unwrap_or(ColorSchemePreference::System)
+        .resolve(system_prefers_dark())
+}
+
+fn prefers_dark_query() -> Option<web_sys::MediaQueryList> {
+    web_sys::window()?.match_media(PREFERS_DARK_QUERY).ok()?
+}
+
+pub fn system_prefers_dark() -> bool {
+    prefers_dark_query().map_or(false, |query| query.matches())
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn stored_preference() -> Option<ColorSchemePreference> {
+    let value = local_storage()?.get_item(STORAGE_KEY).ok()??;
+    ColorSchemePreference::parse(&value)
+}
+
+pub fn load_color_scheme_themes(light: fn() -> Theme, dark: fn() -> Theme) {
+    let scheme = color_scheme().observe_with(|scheme| *scheme);
+    let theme = match scheme {
+        ColorScheme::Light => light(),
+        ColorScheme::Dark => dark(),
+    };
+
+    COLOR_SCHEME_THEMES.with(|themes| {
+        *themes.borrow_mut() = Some(ColorSchemeThemes {
+            light,
+            dark,
+            active: scheme,
+            active_name: theme.name.clone(),
+        })
+    });
+    load_app_theme(theme);
+
+    listen_for_system_changes();
+}
+
+pub fn set_color_scheme_preference(preference: ColorSchemePreference) {
+    if let Some(storage) = local_storage() {
+        if storage.set_item(STORAGE_KEY, preference.as_str()).is_err() {
+            log!("seed_style warning: could not save the color scheme preference");
+        }
+    }
+    color_scheme_preference().update(|current| *current = preference);
+    update_color_scheme();
+}
+
+fn update_color_scheme() {
+    let scheme = color_scheme_preference()
+        .observe_with(|preference| preference.resolve(system_prefers_dark()));
+    if color_scheme().observe_with(|current| *current != scheme) {
+        color_scheme().update(|current| *current = scheme);
+    }
+
+    let swap = COLOR_SCHEME_THEMES.with(|themes| {
+        let mut themes = themes.borrow_mut();
+        let themes = themes.as_mut().filter(|themes| themes.active != scheme)?;
+        let theme = match scheme {
+            ColorScheme::Light => (themes.light)(),
+            ColorScheme::Dark => (themes.dark)(),
+        };
+        themes.active = scheme;
+        let old_name = std::mem::replace(&mut themes.active_name, theme.name.clone());
+        Some((old_name, theme))
+    });
+
+    if let Some((old_name, theme)) = swap {
+        change_theme_with_name(&old_name, theme);
+    }
+}
+
+// The listener lives as long as the app, so it is registered once and leaked.
+fn listen_for_system_changes() {
+    if LISTENING.with(|listening| listening.replace(true)) {
+        return;
+    }
+    if let Some(query) = prefers_dark_query() {
+        let on_change = Closure::wrap(Box::new(|_: web_sys::Event| {
+            if color_scheme_preference().observe_with(|p| *p == ColorSchemePreference::System) {
+                update_color_scheme();
+            }
+        }) as Box<dyn FnMut(web_sys::Event)>);
+
+        if query
+            .add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())
+            .is_err()
+        {
+            log!("seed_style warning: could not listen for color scheme changes");
+        }
+        on_change.forget();
+    }
+}
@@ -1,5 +1,6 @@
 use super::layout::*;
 use super::measures::{pc, px};
+use super::breakpoints::viewport_at_least;
 use super::theme::*;
 use super::*;
 use crate::style::s;
@@ -150,13 +151,7 @@ where
                     self.layouts_hm.get(bp_key),
                 )
             })
-            .find(|(bp_pair, _layout)| match bp_pair {
-                (lower, _) => window()
-                    .match_media(&format!("(min-width: {}px)", lower))
-                    .unwrap()
-                    .unwrap()
-                    .matches(),
-            });
+            .find(|((lower, _), _layout)| viewport_at_least(*lower));
 
         if let Some((_bp_pair, Some(idx))) = opt_layout {
             self.render_layout(*idx, model)
@@ -1,4 +1,5 @@
 use super::measures::*;
+use crate::style::media_query::MediaQuery;
 use crate::style::{CssValueTrait, Style, UpdateStyle};
 use derive_more::Display;
 
@@ -723,6 +724,30 @@ pub enum CssZIndex {
     StringValue(String),
 }
 
+#[derive(Display, Clone, Debug, CssStyleMacro)]
+#[display(fmt = "container-type: {};")]
+pub enum CssContainerType {
+    #[display(fmt = "normal")]
+    Normal,
+    #[display(fmt = "size")]
+    Size,
+    #[display(fmt = "inline-size")]
+    InlineSize,
+    #[display(fmt = "inherit")]
+    Inherit,
+    StringValue(String),
+}
+
+#[derive(Display, Clone, Debug, CssStyleMacro)]
+#[display(fmt = "container-name: {};")]
+pub enum CssContainerName {
+    #[display(fmt = "none")]
+    None,
+    #[display(fmt = "inherit")]
+    Inherit,
+    StringValue(String),
+}
+
 #[derive(Display, Clone, Debug, CssStyleMacro)]
 #[short_prop = "m"]
 #[display(fmt = "margin: {};")]
@@ -2599,11 +2624,132 @@ pub fn trim_css_ends<T:ToString>(property:T, name: &str) -> String {
 }
 
 
+// A media query without the `@media` prefix, e.g. `(min-width: 600px)`.
 #[derive(Debug, Clone)]
 pub struct CssMedia(pub String);
 
+impl From<MediaQuery> for CssMedia {
+    fn from(query: MediaQuery) -> Self {
+        CssMedia(query.to_string())
+    }
+}
+
 impl CssMedia {
     pub fn render(&self) -> String {
         format!("@media {}", self.0)
     }
 }
+
+// Css custom properties, i.e. `--brand: #f00;` declarations and `var(--brand)` references.
+
+pub(crate) fn custom_property_name(name: &str) -> String {
+    if name.starts_with("--") {
+        name.to_string()
+    } else {
+        format!("--{}", name)
+    }
+}
+
+// Anything that can be the value of a custom property or a `var()` fallback.
+pub trait CustomPropertyValue {
+    fn custom_property_value(&self) -> String;
+}
+
+impl<T> CustomPropertyValue for T
+where
+    T: CssValueTrait,
+{
+    fn custom_property_value(&self) -> String {
+        self.value_only()
+    }
+}
+
+impl CustomPropertyValue for &str {
+    fn custom_property_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl CustomPropertyValue for String {
+    fn custom_property_value(&self) -> String {
+        self.clone()
+    }
+}
+
+impl CustomPropertyValue for ExactLength {
+    fn custom_property_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl CustomPropertyValue for Percent {
+    fn custom_property_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl CustomPropertyValue for CssVar {
+    fn custom_property_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CssCustomProperty {
+    pub name: String,
+    pub value: String,
+}
+
+impl std::fmt::Display for CssCustomProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {};", self.name, self.value)
+    }
+}
+
+impl CssValueTrait for CssCustomProperty {
+    fn value_only(&self) -> String {
+        self.value.clone()
+    }
+}
+
+// A reference to a custom property, usable with any property
+// e.g. s().color(var("--brand").fallback("red"))
+#[derive(Clone, Debug)]
+pub struct CssVar {
+    pub name: String,
+    pub fallback: Option<String>,
+}
+
+pub fn var(name: &str) -> CssVar {
+    CssVar {
+        name: custom_property_name(name),
+        fallback: None,
+    }
+}
+
+impl CssVar {
+    pub fn fallback<V: CustomPropertyValue>(mut self, val: V) -> CssVar {
+        self.fallback = Some(val.custom_property_value());
+        self
+    }
+}
+
+impl std::fmt::Display for CssVar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(fallback) = &self.fallback {
+            write!(f, "var({}, {})", self.name, fallback)
+        } else {
+            write!(f, "var({})", self.name)
+        }
+    }
+}
+
+impl<P> UpdateStyle<P> for CssVar
+where
+    P: 'static + Clone + CssValueTrait + for<'a> From<&'a str>,
+{
+    fn update_style(self, style: &mut Style) {
+        let val: P = self.to_string().as_str().into();
+        style.add_rule(Box::new(val));
+    }
+}
@@ -0,0 +1,364 @@
+use crate::style::theme::*;
+use crate::style::theme_files::*;
+use serde_json::{Map, Value};
+
+// Import of W3C Design Tokens Community Group files, which is also what Figma Tokens exports.
+//
+// Tokens are mapped by their `$type`, inherited from enclosing groups, and for dimensions by the
+// name of an enclosing group:
+//
+// color                                     -> colors
+// dimension in a `space`/`spacing` group    -> spaces
+// dimension in a `size`/`sizing` group      -> sizes
+// dimension in a `fontSize` group           -> font_sizes
+// dimension in a `radius`/`radii` group     -> border radii
+// dimension in a `breakpoint` group         -> breakpoint_scale
+// shadow                                    -> shadows
+// transition                                -> transitions
+//
+// Every token except breakpoints becomes an alias keyed by its dotted path, for instance
+// `color.brand.primary`, which is mapped onto an alias enum through `ThemeAliases`.
+// References such as `{color.base.red}` are resolved. Anything that cannot be placed
+// is listed in `DesignTokensImport::unmapped`.
+
+#[derive(Debug)]
+pub struct DesignTokensImport {
+    pub theme: Theme,
+    pub unmapped: Vec<UnmappedToken>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmappedToken {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Copy)]
+enum TokenCategory {
+    Color,
+    Space,
+    Size,
+    FontSize,
+    Radius,
+    Breakpoint,
+    Shadow,
+    Transition,
+}
+
+struct Token<'a> {
+    path: String,
+    token_type: Option<String>,
+    value: &'a Value,
+}
+
+impl Theme {
+    pub fn from_design_tokens(
+        name: &str,
+        src: &str,
+        aliases: &ThemeAliases,
+    ) -> Result<DesignTokensImport, ThemeFileError> {
+        let root: Value = serde_json::from_str(src).map_err(ThemeFileError::Json)?;
+
+        let mut tokens = vec![];
+        collect_tokens(&root, &mut vec![], None, &mut tokens);
+
+        let mut file = ThemeFile {
+            name: name.to_string(),
+            ..ThemeFile::default()
+        };
+        let mut unmapped = vec![];
+
+        for token in &tokens {
+            let category = if let Some(category) = token_category(token) {
+                category
+            } else {
+                unmapped.push(UnmappedToken {
+                    path: token.path.clone(),
+                    reason: format!(
+                        "unsupported token type {}",
+                        token.token_type.as_deref().unwrap_or("(none)")
+                    ),
+                });
+                continue;
+            };
+
+            let value =
+                resolve_references(token.value, &root, 0).and_then(|value| match category {
+                    TokenCategory::Shadow => shadow_value(&value),
+                    TokenCategory::Transition => transition_value(&value),
+                    TokenCategory::Color => value.as_str().map(|s| s.to_string()),
+                    _ => dimension_value(&value),
+                });
+
+            let value = if let Some(value) = value {
+                value
+            } else {
+                unmapped.push(UnmappedToken {
+                    path: token.path.clone(),
+                    reason: "value could not be read".to_string(),
+                });
+                continue;
+            };
+
+            let path = token.path.clone();
+            let file_aliases = &mut file.aliases;
+            match category {
+                TokenCategory::Color => {
+                    file_aliases.colors.insert(path, value);
+                }
+                TokenCategory::Space => {
+                    file_aliases.spaces.insert(path, value);
+                }
+                TokenCategory::Size => {
+                    file_aliases.sizes.insert(path, value);
+                }
+                TokenCategory::FontSize => {
+                    file_aliases.font_sizes.insert(path, value);
+                }
+                TokenCategory::Radius => {
+                    file_aliases.radii.insert(path, value);
+                }
+                TokenCategory::Shadow => {
+                    file_aliases.shadows.insert(path, value);
+                }
+                TokenCategory::Transition => {
+                    file_aliases.transitions.insert(path, value);
+                }
+                TokenCategory::Breakpoint => {
+                    if let Ok(px) = value.trim_end_matches("px").parse::<f64>() {
+                        file.scales.breakpoints.push(px as u32);
+                    } else {
+                        unmapped.push(UnmappedToken {
+                            path,
+                            reason: "breakpoints must be px dimensions".to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        file.scales.breakpoints.sort_unstable();
+        file.scales.breakpoints.dedup();
+
+        let (theme, unregistered) = file.to_theme_reporting_unmapped(aliases);
+        for category_and_path in unregistered {
+            let path = category_and_path.splitn(2, '.').nth(1).unwrap_or("");
+            unmapped.push(UnmappedToken {
+                path: path.to_string(),
+                reason: "no alias registered for this path".to_string(),
+            });
+        }
+
+        Ok(DesignTokensImport { theme, unmapped })
+    }
+}
+
+fn token_value(obj: &Map<String, Value>) -> Option<&Value> {
+    // Figma Tokens files use `value` and `type` without the `$`.
+    obj.get("$value").or_else(|| match obj.get("value") {
+        Some(value) if obj.contains_key("type") => Some(value),
+        _ => None,
+    })
+}
+
+fn collect_tokens<'a>(
+    node: &'a Value,
+    path: &mut Vec<String>,
+    inherited_type: Option<String>,
+    tokens: &mut Vec<Token<'a>>,
+) {
+    let obj = if let Value::Object(obj) = node {
+        obj
+    } else {
+        return;
+    };
+
+    let own_type = obj
+        .get("$type")
+        .or_else(|| obj.get("type"))
+        .and_then(|t| t.as_str())
+        .map(|t| t.to_string())
+        .or(inherited_type);
+
+    if let Some(value) = token_value(obj) {
+        tokens.push(Token {
+            path: path.join("."),
+            token_type: own_type,
+            value,
+        });
+        return;
+    }
+
+    for (key, child) in obj {
+        if key.starts_with('$') {
+            continue;
+        }
+        path.push(key.clone());
+        collect_tokens(child, path, own_type.clone(), tokens);
+        path.pop();
+    }
+}
+
+fn token_category(token: &Token) -> Option<TokenCategory> {
+    let in_group = |names: &[&str]| {
+        token.path.split('.').any(|segment| {
+            let segment = segment.to_lowercase().replace('-', "").replace('_', "");
+            names.contains(&segment.as_str())
+        })
+    };
+
+    match token.token_type.as_deref()? {
+        "color" => Some(TokenCategory::Color),
+        "shadow" | "boxShadow" => Some(TokenCategory::Shadow),
+        "transition" => Some(TokenCategory::Transition),
+        "spacing" => Some(TokenCategory::Space),
+        "sizing" => Some(TokenCategory::Size),
+        "fontSizes" => Some(TokenCategory::FontSize),
+        "borderRadius" => Some(TokenCategory::Radius),
+        "dimension" => {
+            if in_group(&["breakpoint", "breakpoints"]) {
+                Some(TokenCategory::Breakpoint)
+            } else if in_group(&["fontsize", "fontsizes"]) {
+                Some(TokenCategory::FontSize)
+            } else if in_group(&["radius", "radii", "borderradius"]) {
+                Some(TokenCategory::Radius)
+            } else if in_group(&["space", "spaces", "spacing"]) {
+                Some(TokenCategory::Space)
+            } else if in_group(&["size", "sizes", "sizing"]) {
+                Some(TokenCategory::Size)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+// Replaces `{group.token}` references, including those nested inside composite values.
+fn resolve_references(value: &Value, root: &Value, depth: usize) -> Option<Value> {
+    if depth > 16 {
+        return None;
+    }
+    match value {
+        Value::String(s) if s.starts_with('{') && s.ends_with('}') => {
+            let mut node = root;
+            for segment in s[1..s.len() - 1].split('.') {
+                node = node.get(segment)?;
+            }
+            let referenced = node.as_object().and_then(token_value)?;
+            resolve_references(referenced, root, depth + 1)
+        }
+        Value::Object(obj) => {
+            let mut resolved = Map::new();
+            for (key, value) in obj {
+                resolved.insert(key.clone(), resolve_references(value, root, depth + 1)?);
+            }
+            Some(Value::Object(resolved))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| resolve_references(item, root, depth + 1))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        other => Some(other.clone()),
+    }
+}
+
+fn plain_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// `"16px"`, `16` or `{ "value": 16, "unit": "px" }`
+fn dimension_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(format!("{}px", n)),
+        Value::Object(obj) => Some(format!(
+            "{}{}",
+            plain_text(obj.get("value")?),
+            obj.get("unit").and_then(|u| u.as_str()).unwrap_or("px")
+        )),
+        _ => None,
+    }
+}
+
+fn duration_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(format!("{}ms", n)),
+        Value::Object(obj) => Some(format!(
+            "{}{}",
+            plain_text(obj.get("value")?),
+            obj.get("unit").and_then(|u| u.as_str()).unwrap_or("ms")
+        )),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn timing_function_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Array(points) if points.len() == 4 => Some(format!(
+            "cubic-bezier({})",
+            points
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn shadow_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(layers) => layers
+            .iter()
+            .map(shadow_value)
+            .collect::<Option<Vec<_>>>()
+            .map(|layers| layers.join(", ")),
+        Value::Object(obj) => {
+            // W3C uses offsetX/offsetY, Figma Tokens x/y and a `type` of innerShadow for inset.
+            let offset_x = obj.get("offsetX").or_else(|| obj.get("x"))?;
+            let offset_y = obj.get("offsetY").or_else(|| obj.get("y"))?;
+            let inset = obj.get("inset").and_then(|i| i.as_bool()).unwrap_or(false)
+                || obj.get("type").and_then(|t| t.as_str()) == Some("innerShadow");
+
+            let mut parts = vec![];
+            if inset {
+                parts.push("inset".to_string());
+            }
+            parts.push(dimension_value(offset_x)?);
+            parts.push(dimension_value(offset_y)?);
+            if let Some(blur) = obj.get("blur") {
+                parts.push(dimension_value(blur)?);
+            }
+            if let Some(spread) = obj.get("spread") {
+                parts.push(dimension_value(spread)?);
+            }
+            parts.push(obj.get("color")?.as_str()?.to_string());
+            Some(parts.join(" "))
+        }
+        _ => None,
+    }
+}
+
+fn transition_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Object(obj) => {
+            let mut parts = vec!["all".to_string(), duration_value(obj.get("duration")?)?];
+            if let Some(timing) = obj.get("timingFunction") {
+                parts.push(timing_function_value(timing)?);
+            }
+            if let Some(delay) = obj.get("delay") {
+                parts.push(duration_value(delay)?);
+            }
+            Some(parts.join(" "))
+        }
+        _ => None,
+    }
+}
@@ -1,3 +1,4 @@
+use super::breakpoints::{viewport_at_least, viewport_at_most};
 use super::theme::*;
 use seed::{prelude::*, *};
 use seed_hooks::use_state;
@@ -29,23 +30,7 @@ pub fn conditionally_skip_rendering<
 
             if let Some(bp_key) = current_breakpoint {
                 let bp_pair = bp_hm.get(bp_key).cloned().unwrap();
-
-                let is_the_same_bp = match bp_pair {
-                    (lower, Some(higher)) => window()
-                        .match_media(&format!(
-                            "(min-width: {}px) and (max-width: {}px",
-                            lower,
-                            higher - 1
-                        ))
-                        .unwrap()
-                        .unwrap()
-                        .matches(),
-                    (lower, _) => window()
-                        .match_media(&format!("(min-width: {}px)", lower))
-                        .unwrap()
-                        .unwrap()
-                        .matches(),
-                };
+                let is_the_same_bp = is_inside_breakpoint(bp_pair);
 
                 if is_the_same_bp {
                     orders.skip();
@@ -58,22 +43,7 @@ pub fn conditionally_skip_rendering<
 
             if need_to_set_bp {
                 let opt_bp = bp_hm.iter().find_map(|(bp_key, bp_pair)| {
-                    let is_this_bp = match bp_pair {
-                        (lower, Some(higher)) => window()
-                            .match_media(&format!(
-                                "(min-width: {}px) and (max-width: {}px)",
-                                lower,
-                                higher - 1
-                            ))
-                            .unwrap()
-                            .unwrap()
-                            .matches(),
-                        (lower, _) => window()
-                            .match_media(&format!("(min-width: {}px)", lower))
-                            .unwrap()
-                            .unwrap()
-                            .matches(),
-                    };
+                    let is_this_bp = is_inside_breakpoint(*bp_pair);
 
                     if is_this_bp {
                         Some(bp_key.clone())
@@ -86,3 +56,11 @@ pub fn conditionally_skip_rendering<
         });
     });
 }
+
+// alias breakpoints are upper bound exclusive
+fn is_inside_breakpoint(bp_pair: (u32, Option<u32>)) -> bool {
+    match bp_pair {
+        (lower, Some(higher)) => viewport_at_least(lower) && viewport_at_most(higher.saturating_sub(1)),
+        (lower, _) => viewport_at_least(lower),
+    }
+}
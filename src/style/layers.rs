@@ -0,0 +1,135 @@
+use crate::style::stylesheet::*;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// Cascade layers.
+//
+// Rules in a later layer win over rules in an earlier one whatever their selector specificity,
+// so resets, theme defaults, components, utilities and overrides can be kept apart without
+// relying on class chains:
+//
+// set_layer_order(&[CascadeLayer::Reset, CascadeLayer::Components, CascadeLayer::Overrides]);
+//
+// s().padding(px(4)).layer(CascadeLayer::Components)
+//     -> @layer components{ .sst-class.seedstyle-..{ .. } }
+// GlobalStyle::new().layer(CascadeLayer::Reset).style("body", s().margin(px(0)))
+//
+// The `@layer` ordering statement is inserted once at the top of the sheet, the first time a
+// layered rule is rendered, using `default_layer_order()` unless `set_layer_order` was called.
+// Calling `set_layer_order` again replaces it. Layers missing from the order come after the
+// listed ones, in the order they first appear. Unlayered rules win over every layer.
+//
+// Third party css can be slotted into a layer with `layered_css`.
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CascadeLayer {
+    Reset,
+    Theme,
+    Components,
+    Utilities,
+    Overrides,
+    Named(String),
+}
+
+impl std::fmt::Display for CascadeLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CascadeLayer::Reset => write!(f, "reset"),
+            CascadeLayer::Theme => write!(f, "theme"),
+            CascadeLayer::Components => write!(f, "components"),
+            CascadeLayer::Utilities => write!(f, "utilities"),
+            CascadeLayer::Overrides => write!(f, "overrides"),
+            CascadeLayer::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl From<&str> for CascadeLayer {
+    fn from(name: &str) -> CascadeLayer {
+        match name {
+            "reset" => CascadeLayer::Reset,
+            "theme" => CascadeLayer::Theme,
+            "components" => CascadeLayer::Components,
+            "utilities" => CascadeLayer::Utilities,
+            "overrides" => CascadeLayer::Overrides,
+            name => CascadeLayer::Named(name.to_string()),
+        }
+    }
+}
+
+impl From<&CascadeLayer> for CascadeLayer {
+    fn from(layer: &CascadeLayer) -> CascadeLayer {
+        layer.clone()
+    }
+}
+
+// owner hash of the ordering statement in the sheet bookkeeping
+const LAYER_ORDER_OWNER: u64 = 0x5eed_1a7e_0000_0001;
+
+thread_local! {
+    static LAYER_ORDER: RefCell<Option<Vec<CascadeLayer>>> = RefCell::new(None);
+}
+
+pub fn default_layer_order() -> Vec<CascadeLayer> {
+    vec![
+        CascadeLayer::Reset,
+        CascadeLayer::Theme,
+        CascadeLayer::Components,
+        CascadeLayer::Utilities,
+        CascadeLayer::Overrides,
+    ]
+}
+
+// Earliest layer first, i.e. the last layer has the highest priority.
+pub fn set_layer_order<L: Into<CascadeLayer> + Clone>(layers: &[L]) {
+    let layers = layers.iter().cloned().map(Into::into).collect::<Vec<_>>();
+    LAYER_ORDER.with(|order| *order.borrow_mut() = Some(layers));
+    insert_layer_order();
+}
+
+pub fn layer_order() -> Vec<CascadeLayer> {
+    LAYER_ORDER
+        .with(|order| order.borrow().clone())
+        .unwrap_or_else(default_layer_order)
+}
+
+pub fn render_layer_order() -> String {
+    let names = layer_order()
+        .iter()
+        .map(|layer| layer.to_string())
+        .collect::<Vec<_>>();
+    format!("@layer {};", names.join(", "))
+}
+
+// Inserts the ordering statement unless it is already in the sheet.
+pub(crate) fn ensure_layer_order() {
+    let inserted = with_stylesheet_backend(|backend| {
+        backend
+            .bookkeeping()
+            .rule_owners
+            .contains(&Some(LAYER_ORDER_OWNER))
+    });
+    if !inserted {
+        insert_layer_order();
+    }
+}
+
+// The statement has to come before any layered rule, so it is kept as the first rule.
+fn insert_layer_order() {
+    if replace_pinned_rule_at_top(LAYER_ORDER_OWNER, &render_layer_order()) {
+        with_stylesheet_backend(|backend| backend.bookkeeping_mut().global_rules += 1);
+    }
+}
+
+// Inserts css written elsewhere, e.g. a third party stylesheet, into `layer`.
+// The same css is only inserted once into a layer, so this can be called from a view.
+pub fn layered_css<L: Into<CascadeLayer>>(layer: L, css: &str) {
+    let layer = layer.into();
+    let mut hasher = DefaultHasher::new();
+    (&layer, css).hash(&mut hasher);
+
+    ensure_layer_order();
+    let rule = format!("@layer {}{{\n{}\n}}", layer, css);
+    insert_pinned_global_rule(hasher.finish(), &rule);
+}
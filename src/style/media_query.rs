@@ -0,0 +1,412 @@
+use crate::style::measures::ExactLength;
+use derive_more::Display;
+
+// Typed media queries.
+//
+// Features are joined with `and`, `or` builds a query list and `not` negates:
+//
+// s().media(media().min_width(px(600)).hover(Hover::Hover))
+//     -> @media (min-width: 600px) and (hover: hover)
+// s().media(media().print().or(media().max_width(px(599))))
+//     -> @media print, (max-width: 599px)
+// s().media(!media().screen().orientation(Orientation::Portrait))
+//     -> @media not screen and (orientation: portrait)
+//
+// Raw strings are still accepted, with or without the `@media` prefix.
+
+#[derive(Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaType {
+    #[display(fmt = "all")]
+    All,
+    #[display(fmt = "screen")]
+    Screen,
+    #[display(fmt = "print")]
+    Print,
+}
+
+#[derive(Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    #[display(fmt = "portrait")]
+    Portrait,
+    #[display(fmt = "landscape")]
+    Landscape,
+}
+
+#[derive(Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hover {
+    #[display(fmt = "none")]
+    None,
+    #[display(fmt = "hover")]
+    Hover,
+}
+
+#[derive(Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pointer {
+    #[display(fmt = "none")]
+    None,
+    #[display(fmt = "coarse")]
+    Coarse,
+    #[display(fmt = "fine")]
+    Fine,
+}
+
+#[derive(Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Contrast {
+    #[display(fmt = "no-preference")]
+    NoPreference,
+    #[display(fmt = "more")]
+    More,
+    #[display(fmt = "less")]
+    Less,
+    #[display(fmt = "custom")]
+    Custom,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SingleMediaQuery {
+    negated: bool,
+    media_type: Option<MediaType>,
+    // rendered features, e.g. `(min-width: 600px)`
+    features: Vec<String>,
+    // query text passed as a string
+    raw: Option<String>,
+}
+
+impl std::fmt::Display for SingleMediaQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(raw) = &self.raw {
+            return if self.negated {
+                write!(f, "not {}", raw)
+            } else {
+                write!(f, "{}", raw)
+            };
+        }
+
+        let mut parts = vec![];
+        match (self.negated, self.media_type) {
+            (true, media_type) => {
+                parts.push(format!("not {}", media_type.unwrap_or(MediaType::All)))
+            }
+            (false, Some(media_type)) => parts.push(media_type.to_string()),
+            (false, None) if self.features.is_empty() => parts.push(MediaType::All.to_string()),
+            (false, None) => {}
+        }
+        parts.extend(self.features.iter().cloned());
+        write!(f, "{}", parts.join(" and "))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaQuery {
+    // a comma separated query list matches if any of its queries match
+    queries: Vec<SingleMediaQuery>,
+}
+
+pub fn media() -> MediaQuery {
+    MediaQuery::default()
+}
+
+impl MediaQuery {
+    fn with_feature(mut self, feature: String) -> MediaQuery {
+        if self.queries.is_empty() {
+            self.queries.push(SingleMediaQuery::default());
+        }
+        for query in &mut self.queries {
+            if let Some(raw) = &mut query.raw {
+                *raw = format!("{} and {}", raw, feature);
+            } else {
+                query.features.push(feature.clone());
+            }
+        }
+        self
+    }
+
+    fn with_type(mut self, media_type: MediaType) -> MediaQuery {
+        if self.queries.is_empty() {
+            self.queries.push(SingleMediaQuery::default());
+        }
+        for query in &mut self.queries {
+            if let Some(raw) = &mut query.raw {
+                *raw = match raw_conditions(raw) {
+                    "" => media_type.to_string(),
+                    conditions => format!("{} and {}", media_type, conditions),
+                };
+            } else {
+                query.media_type = Some(media_type);
+            }
+        }
+        self
+    }
+
+    pub fn all(self) -> MediaQuery {
+        self.with_type(MediaType::All)
+    }
+
+    pub fn screen(self) -> MediaQuery {
+        self.with_type(MediaType::Screen)
+    }
+
+    pub fn print(self) -> MediaQuery {
+        self.with_type(MediaType::Print)
+    }
+
+    pub fn min_width<L: Into<ExactLength>>(self, width: L) -> MediaQuery {
+        self.with_feature(format!("(min-width: {})", width.into()))
+    }
+
+    pub fn max_width<L: Into<ExactLength>>(self, width: L) -> MediaQuery {
+        self.with_feature(format!("(max-width: {})", width.into()))
+    }
+
+    pub fn min_height<L: Into<ExactLength>>(self, height: L) -> MediaQuery {
+        self.with_feature(format!("(min-height: {})", height.into()))
+    }
+
+    pub fn max_height<L: Into<ExactLength>>(self, height: L) -> MediaQuery {
+        self.with_feature(format!("(max-height: {})", height.into()))
+    }
+
+    pub fn orientation(self, orientation: Orientation) -> MediaQuery {
+        self.with_feature(format!("(orientation: {})", orientation))
+    }
+
+    // whether the primary input can hover
+    pub fn hover(self, hover: Hover) -> MediaQuery {
+        self.with_feature(format!("(hover: {})", hover))
+    }
+
+    // whether any input can hover
+    pub fn any_hover(self, hover: Hover) -> MediaQuery {
+        self.with_feature(format!("(any-hover: {})", hover))
+    }
+
+    pub fn pointer(self, pointer: Pointer) -> MediaQuery {
+        self.with_feature(format!("(pointer: {})", pointer))
+    }
+
+    pub fn any_pointer(self, pointer: Pointer) -> MediaQuery {
+        self.with_feature(format!("(any-pointer: {})", pointer))
+    }
+
+    pub fn prefers_reduced_motion(self) -> MediaQuery {
+        self.with_feature("(prefers-reduced-motion: reduce)".to_string())
+    }
+
+    pub fn prefers_contrast(self, contrast: Contrast) -> MediaQuery {
+        self.with_feature(format!("(prefers-contrast: {})", contrast))
+    }
+
+    // Both queries have to match. Lists are combined query by query, `(a, b) and c` is `a and c, b and c`.
+    pub fn and<Q: Into<MediaQuery>>(self, other: Q) -> MediaQuery {
+        let other = other.into();
+        if self.queries.is_empty() {
+            return other;
+        }
+        if other.queries.is_empty() {
+            return self;
+        }
+
+        let mut queries = vec![];
+        for left in &self.queries {
+            for right in &other.queries {
+                queries.extend(and_queries(left, right));
+            }
+        }
+        if queries.is_empty() {
+            // e.g. `screen and print`, rendered as `not all`
+            queries.push(SingleMediaQuery {
+                negated: true,
+                media_type: Some(MediaType::All),
+                ..SingleMediaQuery::default()
+            });
+        }
+        MediaQuery { queries }
+    }
+
+    // Either query has to match, rendered as a comma separated list.
+    pub fn or<Q: Into<MediaQuery>>(mut self, other: Q) -> MediaQuery {
+        self.queries.extend(other.into().queries);
+        self
+    }
+
+    // Negates each query of the list, also available as `!query`. A list can only be negated
+    // query by query in css, so negating a list of several queries is rarely what is wanted.
+    pub fn negate(mut self) -> MediaQuery {
+        if self.queries.is_empty() {
+            self.queries.push(SingleMediaQuery::default());
+        }
+        for query in &mut self.queries {
+            query.negated = !query.negated;
+        }
+        self
+    }
+
+    // The condition of an `@container` rule, which has no media types and joins lists with `or`.
+    pub(crate) fn container_condition(&self) -> String {
+        let condition = |query: &SingleMediaQuery| {
+            let features = if let Some(raw) = &query.raw {
+                raw.clone()
+            } else {
+                query.features.join(" and ")
+            };
+            match (query.negated, query.features.len()) {
+                (false, _) => features,
+                (true, 1) => format!("not {}", features),
+                (true, _) => format!("not ({})", features),
+            }
+        };
+        match self.queries.as_slice() {
+            [query] => condition(query),
+            queries => queries
+                .iter()
+                .map(|query| {
+                    let condition = condition(query);
+                    if condition.starts_with('(') && !condition.contains(" and ") {
+                        condition
+                    } else {
+                        format!("({})", condition)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" or "),
+        }
+    }
+
+    // The full at rule prelude, e.g. `@media (min-width: 600px)`.
+    pub fn render(&self) -> String {
+        format!("@media {}", self)
+    }
+}
+
+impl std::ops::Not for MediaQuery {
+    type Output = MediaQuery;
+
+    fn not(self) -> MediaQuery {
+        self.negate()
+    }
+}
+
+// The queries of `left and right`. css has no media types inside conditions, so a negated
+// query with a type is split with De Morgan, `not screen and (f)` is `print, (not (f))`.
+// Pairs with conflicting media types match nothing and are left out.
+fn and_queries(left: &SingleMediaQuery, right: &SingleMediaQuery) -> Vec<SingleMediaQuery> {
+    let mut queries = vec![];
+    for left in positive_queries(left) {
+        for right in positive_queries(right) {
+            let media_type = match (left.media_type, right.media_type) {
+                (None, other) | (Some(MediaType::All), other) => other,
+                (other, None) | (other, Some(MediaType::All)) => other,
+                (Some(left), Some(right)) if left == right => Some(left),
+                _ => continue,
+            };
+            let mut features = left.features.clone();
+            features.extend(right.features.iter().cloned());
+            queries.push(SingleMediaQuery {
+                negated: false,
+                media_type,
+                features,
+                raw: None,
+            });
+        }
+    }
+    queries
+}
+
+// A query as a list of queries without `not`, matching the same. An empty list matches nothing.
+fn positive_queries(query: &SingleMediaQuery) -> Vec<SingleMediaQuery> {
+    let condition = |features: Vec<String>| SingleMediaQuery {
+        features,
+        ..SingleMediaQuery::default()
+    };
+    if let Some(raw) = &query.raw {
+        // raw queries are kept as written
+        let raw = if query.negated {
+            format!("(not {})", raw)
+        } else {
+            raw.clone()
+        };
+        return vec![condition(vec![raw])];
+    }
+    if !query.negated {
+        return vec![query.clone()];
+    }
+
+    let mut queries = vec![];
+    // `screen` and `print` are the only media types left that match anything
+    match query.media_type {
+        Some(MediaType::Screen) => queries.push(SingleMediaQuery {
+            media_type: Some(MediaType::Print),
+            ..SingleMediaQuery::default()
+        }),
+        Some(MediaType::Print) => queries.push(SingleMediaQuery {
+            media_type: Some(MediaType::Screen),
+            ..SingleMediaQuery::default()
+        }),
+        Some(MediaType::All) | None => {}
+    }
+    match query.features.as_slice() {
+        [] => {}
+        [feature] => queries.push(condition(vec![format!("(not {})", feature)])),
+        features => queries.push(condition(vec![format!(
+            "(not ({}))",
+            features.join(" and ")
+        )])),
+    }
+    queries
+}
+
+// A raw query without its media type, `(min-width: 600px)` for `screen and (min-width: 600px)`.
+fn raw_conditions(raw: &str) -> &str {
+    let raw = raw.trim();
+    let media_type_end = raw.find(' ').unwrap_or_else(|| raw.len());
+    match &raw[..media_type_end] {
+        "all" | "screen" | "print" => raw[media_type_end..]
+            .trim_start()
+            .trim_start_matches("and ")
+            .trim_start(),
+        _ => raw,
+    }
+}
+
+impl std::fmt::Display for MediaQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.queries.is_empty() {
+            return write!(f, "all");
+        }
+        let queries = self
+            .queries
+            .iter()
+            .map(|query| query.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", queries.join(", "))
+    }
+}
+
+impl From<&str> for MediaQuery {
+    fn from(query: &str) -> MediaQuery {
+        let query = query.trim();
+        let query = query.strip_prefix("@media").unwrap_or(query).trim();
+        // a list is kept query by query so features and types can be added to each of them
+        MediaQuery {
+            queries: query
+                .split(',')
+                .map(|query| SingleMediaQuery {
+                    raw: Some(query.trim().to_string()),
+                    ..SingleMediaQuery::default()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<String> for MediaQuery {
+    fn from(query: String) -> MediaQuery {
+        MediaQuery::from(query.as_str())
+    }
+}
+
+impl From<&MediaQuery> for MediaQuery {
+    fn from(query: &MediaQuery) -> MediaQuery {
+        query.clone()
+    }
+}
@@ -0,0 +1,80 @@
+use crate::style::PseudoSelector;
+
+// An+B arguments of `:nth-child()`, `:nth-last-child()`, `:nth-of-type()` and `:nth-last-of-type()`.
+//
+// s().nth_child(3)                          -> :nth-child(3)
+// s().nth_child(Nth::odd())                 -> :nth-child(odd)
+// s().nth_child(Nth::new(-1, 3))            -> :nth-child(-n+3)
+// s().nth_child(Nth::even().of(".visible")) -> :nth-child(even of .visible)
+//
+// `of` is only valid for `nth_child` and `nth_last_child`.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Nth {
+    a: i32,
+    b: i32,
+    of: Option<String>,
+}
+
+impl Nth {
+    // every `a`th element, offset by `b`
+    pub fn new(a: i32, b: i32) -> Nth {
+        Nth { a, b, of: None }
+    }
+
+    pub fn odd() -> Nth {
+        Nth::new(2, 1)
+    }
+
+    pub fn even() -> Nth {
+        Nth::new(2, 0)
+    }
+
+    // Only counts elements matching `selector`, which can also be a `&Style`.
+    pub fn of<S: PseudoSelector>(mut self, selector: S) -> Nth {
+        self.of = Some(selector.pseudo_selector());
+        self
+    }
+}
+
+impl std::fmt::Display for Nth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.a, self.b) {
+            (2, 1) => write!(f, "odd")?,
+            (2, 0) => write!(f, "even")?,
+            (0, b) => write!(f, "{}", b)?,
+            (a, b) => {
+                match a {
+                    1 => write!(f, "n")?,
+                    -1 => write!(f, "-n")?,
+                    a => write!(f, "{}n", a)?,
+                }
+                if b != 0 {
+                    write!(f, "{:+}", b)?;
+                }
+            }
+        }
+        if let Some(of) = &self.of {
+            write!(f, " of {}", of)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<i32> for Nth {
+    fn from(index: i32) -> Nth {
+        Nth::new(0, index)
+    }
+}
+
+impl From<u32> for Nth {
+    fn from(index: u32) -> Nth {
+        Nth::new(0, index as i32)
+    }
+}
+
+impl From<usize> for Nth {
+    fn from(index: usize) -> Nth {
+        Nth::new(0, index as i32)
+    }
+}
@@ -1,6 +1,8 @@
 use super::theme::*;
 use super::*;
 
+// The values are set here rather than with `#[theme(value = "..")]` on the aliases below, which
+// would store them as strings and render `#FFF5F5` instead of the typed `#fff5f5`.
 pub fn default_colors_theme() -> Theme {
     Theme::default()
         .set_color(seed_colors::Base::Black, CssColor::Hex(0x000000))
@@ -99,14 +101,16 @@ pub fn default_colors_theme() -> Theme {
 
 pub mod seed_colors {
     use super::*;
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    use seed_style_macros::ThemeAlias;
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Base {
         White,
         Black,
     }
-    impl ColorTheme for Base {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Red {
         No1,
         No2,
@@ -118,8 +122,8 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Red {}
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Blue {
         No1,
         No2,
@@ -131,8 +135,8 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Blue {}
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Green {
         No1,
         No2,
@@ -144,9 +148,9 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Green {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Orange {
         No1,
         No2,
@@ -158,9 +162,9 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Orange {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Pink {
         No1,
         No2,
@@ -172,9 +176,9 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Pink {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Teal {
         No1,
         No2,
@@ -186,9 +190,9 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Teal {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Indigo {
         No1,
         No2,
@@ -200,8 +204,8 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Indigo {}
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Purple {
         No1,
         No2,
@@ -213,9 +217,9 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Purple {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Gray {
         No1,
         No2,
@@ -227,9 +231,9 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Gray {}
 
-    #[derive(Hash, PartialEq, Eq, Clone)]
+    #[derive(Hash, PartialEq, Eq, Clone, ThemeAlias)]
+    #[theme(color)]
     pub enum Yellow {
         No1,
         No2,
@@ -241,5 +245,4 @@ pub mod seed_colors {
         No8,
         No9,
     }
-    impl ColorTheme for Yellow {}
 }
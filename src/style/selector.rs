@@ -0,0 +1,277 @@
+use crate::style::{log_warning, Pseudo, PseudoSelector, Style};
+
+// Typed selectors.
+//
+// `this()` is the element the style is attached to, other styles stand for the marker class
+// they give their elements, so rules can depend on the state of other styled elements:
+//
+// let card = s().name("card").padding(px(8));
+// let icon = s()
+//     .color("gray")
+//     .target(Selector::from(&card).pseudo(Pseudo::Hover).descendant(this()));
+//     -> .seedmark-card-..:hover .sst-class.seedstyle-..{ color: gray; }
+//
+// s().color("red").target(element("ul").child(element("li").pseudo(Pseudo::FirstChild)).child(this()))
+//     -> ul > li:first-child > .sst-class.seedstyle-..
+//
+// A target without `this()` selects inside the styled element, `attr("open")` -> `& [open]`.
+// Strings are taken as written, with `&` standing for the styled element.
+//
+// The combinator methods take selectors too, `s().child_of(element("ul").class("open"))`
+// -> `ul.open > .sst-class.seedstyle-..`.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectorCombinator {
+    Descendant,
+    Child,
+    Adjacent,
+    Sibling,
+}
+
+impl std::fmt::Display for SelectorCombinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectorCombinator::Descendant => write!(f, " "),
+            SelectorCombinator::Child => write!(f, " > "),
+            SelectorCombinator::Adjacent => write!(f, " + "),
+            SelectorCombinator::Sibling => write!(f, " ~ "),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selector {
+    // the element the style is attached to, `&`
+    This,
+    Class(String),
+    Id(String),
+    Element(String),
+    // the inside of `[..]`
+    Attribute(String),
+    Pseudo(Pseudo),
+    // simple selectors all matching the same element, e.g. `li.active:hover`
+    Compound(Vec<Selector>),
+    Combined(Box<Selector>, SelectorCombinator, Box<Selector>),
+    List(Vec<Selector>),
+    Raw(String),
+}
+
+pub fn this() -> Selector {
+    Selector::This
+}
+
+pub fn class(name: &str) -> Selector {
+    Selector::Class(name.to_string())
+}
+
+pub fn id(name: &str) -> Selector {
+    Selector::Id(name.to_string())
+}
+
+pub fn element(name: &str) -> Selector {
+    Selector::Element(name.to_string())
+}
+
+// `[name]`
+pub fn attr(name: &str) -> Selector {
+    Selector::Attribute(name.to_string())
+}
+
+// `[name="value"]`
+pub fn attr_eq(name: &str, value: &str) -> Selector {
+    Selector::Attribute(format!("{}=\"{}\"", name, value.replace('"', "\\\"")))
+}
+
+impl Selector {
+    // Adds a simple selector to the element matched last, `element("li").class("active")` -> `li.active`.
+    pub(crate) fn compound(self, part: Selector) -> Selector {
+        match self {
+            Selector::Compound(mut parts) => {
+                parts.push(part);
+                Selector::Compound(parts)
+            }
+            Selector::Combined(left, combinator, right) => {
+                Selector::Combined(left, combinator, Box::new(right.compound(part)))
+            }
+            Selector::List(selectors) => Selector::List(
+                selectors
+                    .into_iter()
+                    .map(|selector| selector.compound(part.clone()))
+                    .collect(),
+            ),
+            selector => Selector::Compound(vec![selector, part]),
+        }
+    }
+
+    pub fn class(self, name: &str) -> Selector {
+        self.compound(class(name))
+    }
+
+    pub fn id(self, name: &str) -> Selector {
+        self.compound(id(name))
+    }
+
+    pub fn attr(self, name: &str) -> Selector {
+        self.compound(attr(name))
+    }
+
+    pub fn attr_eq(self, name: &str, value: &str) -> Selector {
+        self.compound(attr_eq(name, value))
+    }
+
+    pub fn pseudo(self, pseudo: Pseudo) -> Selector {
+        self.compound(Selector::Pseudo(pseudo))
+    }
+
+    // The marker class of another style, `element("a").style(&link_style)`.
+    pub fn style(self, style: &Style) -> Selector {
+        self.compound(Selector::from(style))
+    }
+
+    fn combine<S: Into<Selector>>(self, combinator: SelectorCombinator, other: S) -> Selector {
+        Selector::Combined(Box::new(self), combinator, Box::new(other.into()))
+    }
+
+    // `self other`
+    pub fn descendant<S: Into<Selector>>(self, other: S) -> Selector {
+        self.combine(SelectorCombinator::Descendant, other)
+    }
+
+    // `self > other`
+    pub fn child<S: Into<Selector>>(self, other: S) -> Selector {
+        self.combine(SelectorCombinator::Child, other)
+    }
+
+    // `self + other`
+    pub fn adjacent<S: Into<Selector>>(self, other: S) -> Selector {
+        self.combine(SelectorCombinator::Adjacent, other)
+    }
+
+    // `self ~ other`
+    pub fn sibling<S: Into<Selector>>(self, other: S) -> Selector {
+        self.combine(SelectorCombinator::Sibling, other)
+    }
+
+    // `self, other`
+    pub fn or<S: Into<Selector>>(self, other: S) -> Selector {
+        match (self, other.into()) {
+            (Selector::List(mut left), Selector::List(right)) => {
+                left.extend(right);
+                Selector::List(left)
+            }
+            (Selector::List(mut left), right) => {
+                left.push(right);
+                Selector::List(left)
+            }
+            (left, right) => Selector::List(vec![left, right]),
+        }
+    }
+
+    fn contains_this(&self) -> bool {
+        match self {
+            Selector::This => true,
+            Selector::Raw(raw) => raw.contains('&'),
+            Selector::Compound(parts) => parts.iter().any(Selector::contains_this),
+            Selector::Combined(left, _, right) => left.contains_this() || right.contains_this(),
+            Selector::List(selectors) => selectors.iter().any(Selector::contains_this),
+            _ => false,
+        }
+    }
+
+    // Whether `this()` appears at most at the start of each alternative.
+    fn only_starts_with_this(&self) -> bool {
+        match self {
+            Selector::Raw(raw) => !raw.trim_start().trim_start_matches('&').contains('&'),
+            Selector::Compound(parts) => parts.iter().skip(1).all(|part| !part.contains_this()),
+            Selector::Combined(left, _, right) => {
+                left.only_starts_with_this() && !right.contains_this()
+            }
+            Selector::List(selectors) => selectors.iter().all(Selector::only_starts_with_this),
+            _ => true,
+        }
+    }
+
+    // The selector with `this()` replaced by `this`.
+    pub(crate) fn render_parts(&self, this: &str) -> String {
+        match self {
+            Selector::This => this.to_string(),
+            Selector::Class(name) => format!(".{}", name),
+            Selector::Id(name) => format!("#{}", name),
+            Selector::Element(name) => name.clone(),
+            Selector::Attribute(attribute) => format!("[{}]", attribute),
+            Selector::Pseudo(pseudo) => pseudo.render(),
+            Selector::Compound(parts) => parts.iter().map(|part| part.render_parts(this)).collect(),
+            Selector::Combined(left, combinator, right) => format!(
+                "{}{}{}",
+                left.render_parts(this),
+                combinator,
+                right.render_parts(this)
+            ),
+            Selector::List(selectors) => selectors
+                .iter()
+                .map(|selector| selector.render_parts(this))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Selector::Raw(raw) => raw.replace('&', this),
+        }
+    }
+
+    // The selector with `this()` replaced by `this`. Each alternative of a list that does not
+    // mention `this()` selects inside it.
+    pub fn render_for(&self, this: &str) -> String {
+        match self {
+            Selector::List(selectors) => selectors
+                .iter()
+                .map(|selector| selector.render_for(this))
+                .collect::<Vec<_>>()
+                .join(", "),
+            selector if selector.contains_this() => selector.render_parts(this),
+            selector => format!("{} {}", this, selector.render_parts(this)),
+        }
+    }
+}
+
+impl std::fmt::Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render_parts("&"))
+    }
+}
+
+// Inside a pseudo-class `this()` can only start the selector, where it stands for the element
+// the pseudo-class is on: `.has(this().child(element("img")))` -> `:has(> img)`,
+// `.not(this().class("active"))` -> `:not(.active)`.
+impl PseudoSelector for Selector {
+    fn pseudo_selector(&self) -> String {
+        if !self.only_starts_with_this() {
+            log_warning(format!(
+                "seed_style warning: `this()` can only start a selector passed to a pseudo-class, `{}`",
+                self
+            ));
+        }
+        self.render_parts("").trim().to_string()
+    }
+}
+
+impl From<&str> for Selector {
+    fn from(selector: &str) -> Selector {
+        Selector::Raw(selector.to_string())
+    }
+}
+
+impl From<String> for Selector {
+    fn from(selector: String) -> Selector {
+        Selector::Raw(selector)
+    }
+}
+
+impl From<&Style> for Selector {
+    fn from(style: &Style) -> Selector {
+        Selector::Class(style.class_name())
+    }
+}
+
+impl From<&Selector> for Selector {
+    fn from(selector: &Selector) -> Selector {
+        selector.clone()
+    }
+}
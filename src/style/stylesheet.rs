@@ -0,0 +1,612 @@
+use crate::style::generated_class_hash;
+use seed::{prelude::*, *};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+
+// All generated css passes through a `StyleSheetBackend` on its way to a stylesheet.
+//
+// By default rules are inserted into the CSSOM of the first `<style>` element in `<head>`.
+// A different backend can be chosen at startup with `use_stylesheet_backend()`, for instance
+// to target a specific `<style>` element, an iframe document, a shadow root or no DOM at all
+// (server side rendering and plain `cargo test`).
+//
+// The backend also owns the bookkeeping of which style hashes have already been inserted,
+// which of them are still referenced and which rules belong to which hash.
+pub trait StyleSheetBackend {
+    fn rules_length(&self) -> u32;
+
+    fn insert_rule(&mut self, rule: &str, index: u32);
+
+    fn delete_rule(&mut self, index: u32);
+
+    fn html_class(&self) -> Option<String>;
+
+    fn set_html_class(&mut self, class_name: &str);
+
+    fn bookkeeping(&self) -> &StyleBookkeeping;
+
+    fn bookkeeping_mut(&mut self) -> &mut StyleBookkeeping;
+
+    // The collected stylesheet text, for backends that are not backed by a live CSSOM.
+    fn css_text(&self) -> Option<String> {
+        None
+    }
+
+    // The class attributes of the styled elements currently in the document, for backends that
+    // have one. Their styles are kept by `collect_unused_styles()` even if a render skipped them.
+    fn mounted_classes(&self) -> Option<Vec<String>> {
+        None
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct StyleBookkeeping {
+    // hashes of every style whose rules have been inserted.
+    pub styles_used: HashSet<u64>,
+    // hashes attached to an element since the last `collect_unused_styles()`.
+    pub styles_referenced: HashSet<u64>,
+    // the owning style hash of each rule in the sheet, `None` for global and foreign rules.
+    pub rule_owners: Vec<Option<u64>>,
+    // owners whose rules are kept until replaced, e.g. theme variable blocks.
+    pub pinned: HashSet<u64>,
+    // global rules at the top of the sheet, further global rules are inserted after them.
+    pub global_rules: u32,
+}
+
+thread_local! {
+    static STYLESHEET_BACKEND: RefCell<Option<Box<dyn StyleSheetBackend>>> = RefCell::new(None);
+}
+
+pub fn use_stylesheet_backend<B: StyleSheetBackend + 'static>(backend: B) {
+    STYLESHEET_BACKEND.with(|current| *current.borrow_mut() = Some(Box::new(backend)));
+}
+
+// Convenience for headless rendering, collects all rules in memory.
+pub fn use_css_registry() {
+    use_stylesheet_backend(InMemoryBackend::default());
+}
+
+pub fn with_stylesheet_backend<F, R>(func: F) -> R
+where
+    F: FnOnce(&mut dyn StyleSheetBackend) -> R,
+{
+    STYLESHEET_BACKEND.with(|current| {
+        let mut current = current.borrow_mut();
+        let backend = current.get_or_insert_with(|| Box::new(DomBackend::new()));
+        func(backend.as_mut())
+    })
+}
+
+// The full stylesheet text collected so far, rules are in cascade order.
+// Empty when the current backend writes directly to the DOM.
+pub fn rendered_css() -> String {
+    with_stylesheet_backend(|backend| backend.css_text().unwrap_or_default())
+}
+
+// The class that would have been set on `<html>` by global styles,
+// needed when server rendering so that global rules apply.
+pub fn rendered_html_class() -> Option<String> {
+    with_stylesheet_backend(|backend| backend.html_class())
+}
+
+// Removes the rules of every generated class that has not been attached to an element
+// since the previous collection, and forgets their hashes so they are re-inserted if used again.
+//
+// Styles are referenced when a view attaches them, so call this straight after a view render,
+// for instance from `orders.after_next_render` or on an interval. Backends with a document also
+// keep the styles of elements that are still mounted, such as parts of the view that the render
+// skipped. Without a document those styles are collected.
+//
+// Returns the number of styles that were removed.
+pub fn collect_unused_styles() -> usize {
+    with_stylesheet_backend(|backend| {
+        let mut referenced = std::mem::take(&mut backend.bookkeeping_mut().styles_referenced);
+        if let Some(classes) = backend.mounted_classes() {
+            referenced.extend(
+                classes
+                    .iter()
+                    .flat_map(|classes| classes.split_whitespace())
+                    .filter_map(generated_class_hash),
+            );
+        }
+        let pinned = &backend.bookkeeping().pinned;
+
+        let stale = backend
+            .bookkeeping()
+            .rule_owners
+            .iter()
+            .flatten()
+            .filter(|hash| !referenced.contains(hash) && !pinned.contains(hash))
+            .cloned()
+            .collect::<HashSet<u64>>();
+
+        let owners = backend.bookkeeping().rule_owners.clone();
+        for (index, owner) in owners.iter().enumerate().rev() {
+            if let Some(owner) = owner {
+                if stale.contains(owner) {
+                    backend.delete_rule(index as u32);
+                    backend.bookkeeping_mut().rule_owners.remove(index);
+                }
+            }
+        }
+
+        for hash in &stale {
+            backend.bookkeeping_mut().styles_used.remove(hash);
+        }
+
+        stale.len()
+    })
+}
+
+pub(crate) fn style_already_created(hash: &u64) -> bool {
+    with_stylesheet_backend(|backend| backend.bookkeeping().styles_used.contains(hash))
+}
+
+pub(crate) fn mark_style_created(hash: u64) {
+    with_stylesheet_backend(|backend| {
+        backend.bookkeeping_mut().styles_used.insert(hash);
+    })
+}
+
+pub(crate) fn mark_style_referenced(hash: u64) {
+    with_stylesheet_backend(|backend| {
+        backend.bookkeeping_mut().styles_referenced.insert(hash);
+    })
+}
+
+fn insert_owned_rule(
+    backend: &mut dyn StyleSheetBackend,
+    rule: &str,
+    index: u32,
+    owner: Option<u64>,
+) {
+    // rules that were not inserted by us, e.g. css already in the style element.
+    let length_before = backend.rules_length() as usize;
+    let rule_owners = &mut backend.bookkeeping_mut().rule_owners;
+    while rule_owners.len() < length_before {
+        rule_owners.insert(0, None);
+    }
+
+    backend.insert_rule(rule, index);
+
+    if backend.rules_length() as usize > length_before {
+        let rule_owners = &mut backend.bookkeeping_mut().rule_owners;
+        let index = (index as usize).min(rule_owners.len());
+        rule_owners.insert(index, owner);
+    }
+}
+
+fn insert_global_rule(backend: &mut dyn StyleSheetBackend, rule: &str, owner: Option<u64>) -> bool {
+    let length_before = backend.rules_length();
+    let index = backend.bookkeeping().global_rules;
+    insert_owned_rule(backend, rule, index, owner);
+    let inserted = backend.rules_length() > length_before;
+    if inserted {
+        backend.bookkeeping_mut().global_rules += 1;
+    }
+    inserted
+}
+
+pub(crate) fn insert_global_css_rule(rule: &str) {
+    with_stylesheet_backend(|backend| {
+        insert_global_rule(backend, rule, None);
+    })
+}
+
+// Inserts a global rule that is never collected, unless one owned by `owner` is in the sheet.
+pub(crate) fn insert_pinned_global_rule(owner: u64, rule: &str) {
+    with_stylesheet_backend(|backend| {
+        if backend.bookkeeping().rule_owners.contains(&Some(owner)) {
+            return;
+        }
+        if insert_global_rule(backend, rule, Some(owner)) {
+            backend.bookkeeping_mut().pinned.insert(owner);
+        }
+    })
+}
+
+pub(crate) fn append_css_rule(rule: &str, owner: u64) {
+    with_stylesheet_backend(|backend| {
+        let index = backend.rules_length();
+        insert_owned_rule(backend, rule, index, Some(owner))
+    })
+}
+
+// Inserts a rule that is never collected, replacing the rule owned by `old_owner` in place
+// so that swapping it keeps its position in the cascade.
+pub(crate) fn replace_pinned_rule(old_owner: u64, owner: u64, rule: &str) {
+    with_stylesheet_backend(|backend| {
+        let position = backend
+            .bookkeeping()
+            .rule_owners
+            .iter()
+            .position(|existing| *existing == Some(old_owner));
+
+        let index = if let Some(index) = position {
+            backend.delete_rule(index as u32);
+            backend.bookkeeping_mut().rule_owners.remove(index);
+            index as u32
+        } else {
+            backend.rules_length()
+        };
+
+        let bookkeeping = backend.bookkeeping_mut();
+        bookkeeping.pinned.remove(&old_owner);
+        bookkeeping.pinned.insert(owner);
+
+        insert_owned_rule(backend, rule, index, Some(owner))
+    })
+}
+
+// Like `replace_pinned_rule` for a rule that has to stay first in the sheet, such as the
+// `@layer` ordering statement. Returns whether the rule was newly added.
+pub(crate) fn replace_pinned_rule_at_top(owner: u64, rule: &str) -> bool {
+    with_stylesheet_backend(|backend| {
+        let position = backend
+            .bookkeeping()
+            .rule_owners
+            .iter()
+            .position(|existing| *existing == Some(owner));
+
+        if let Some(index) = position {
+            backend.delete_rule(index as u32);
+            backend.bookkeeping_mut().rule_owners.remove(index);
+        }
+        backend.bookkeeping_mut().pinned.insert(owner);
+
+        insert_owned_rule(backend, rule, 0, Some(owner));
+        position.is_none()
+    })
+}
+
+pub(crate) fn current_html_root_class() -> Option<String> {
+    with_stylesheet_backend(|backend| backend.html_class())
+}
+
+pub(crate) fn set_html_root_class(class_name: &str) {
+    with_stylesheet_backend(|backend| backend.set_html_class(class_name))
+}
+
+// Inserts rules into a `<style>` element's CSSOM.
+//
+// `DomBackend::new()` uses the first `<style>` element in `<head>`, creating one if needed.
+// `DomBackend::for_document()` does the same in another document, such as an iframe's.
+pub struct DomBackend {
+    document: web_sys::Document,
+    style_element: Option<web_sys::HtmlStyleElement>,
+    bookkeeping: StyleBookkeeping,
+}
+
+impl DomBackend {
+    pub fn new() -> DomBackend {
+        DomBackend::for_document(document())
+    }
+
+    pub fn for_document(document: web_sys::Document) -> DomBackend {
+        DomBackend {
+            document,
+            style_element: None,
+            bookkeeping: StyleBookkeeping::default(),
+        }
+    }
+
+    pub fn with_style_element(style_element: web_sys::HtmlStyleElement) -> DomBackend {
+        DomBackend {
+            document: style_element.owner_document().unwrap_or_else(document),
+            style_element: Some(style_element),
+            bookkeeping: StyleBookkeeping::default(),
+        }
+    }
+
+    fn existing_style_element(&self) -> Option<web_sys::HtmlStyleElement> {
+        if let Some(style_elem) = &self.style_element {
+            Some(style_elem.clone())
+        } else {
+            self.document
+                .get_elements_by_tag_name("head")
+                .item(0)
+                .and_then(|head_elem| head_elem.get_elements_by_tag_name("style").item(0))
+                .and_then(|style_elem| style_elem.dyn_into::<web_sys::HtmlStyleElement>().ok())
+        }
+    }
+
+    fn stylesheet(&mut self) -> web_sys::CssStyleSheet {
+        if self.style_element.is_none() {
+            let style_elem = if let Some(style_elem) = self.existing_style_element() {
+                style_elem
+            } else {
+                let head_elem = self
+                    .document
+                    .get_elements_by_tag_name("head")
+                    .item(0)
+                    .unwrap();
+                let style_elem = self
+                    .document
+                    .create_element("style")
+                    .unwrap()
+                    .dyn_into::<web_sys::HtmlStyleElement>()
+                    .unwrap();
+
+                let _ = head_elem.append_child(&style_elem);
+                style_elem
+            };
+            self.style_element = Some(style_elem);
+        }
+
+        self.style_element
+            .as_ref()
+            .unwrap()
+            .sheet()
+            .unwrap()
+            .dyn_into::<web_sys::CssStyleSheet>()
+            .unwrap()
+    }
+
+    fn html_elem(&self) -> Option<web_sys::Element> {
+        self.document.get_elements_by_tag_name("html").item(0)
+    }
+}
+
+impl Default for DomBackend {
+    fn default() -> Self {
+        DomBackend::new()
+    }
+}
+
+impl StyleSheetBackend for DomBackend {
+    fn rules_length(&self) -> u32 {
+        if let Some(style_elem) = self.existing_style_element() {
+            style_elem
+                .sheet()
+                .unwrap()
+                .dyn_into::<web_sys::CssStyleSheet>()
+                .unwrap()
+                .css_rules()
+                .unwrap()
+                .length()
+        } else {
+            // no style element has been created yet.
+            0
+        }
+    }
+
+    fn insert_rule(&mut self, rule: &str, index: u32) {
+        let res = self.stylesheet().insert_rule_with_index(rule, index);
+        if let Err(err) = res {
+            log!("error inserting style: ", err, rule);
+        }
+    }
+
+    fn delete_rule(&mut self, index: u32) {
+        let res = self.stylesheet().delete_rule(index);
+        if let Err(err) = res {
+            log!("error deleting style: ", err);
+        }
+    }
+
+    fn html_class(&self) -> Option<String> {
+        self.html_elem()
+            .and_then(|html_root_elem| html_root_elem.get_attribute("class"))
+    }
+
+    fn set_html_class(&mut self, class_name: &str) {
+        if let Some(html_root_elem) = self.html_elem() {
+            let _ = html_root_elem.set_attribute("class", class_name);
+        }
+    }
+
+    fn bookkeeping(&self) -> &StyleBookkeeping {
+        &self.bookkeeping
+    }
+
+    fn bookkeeping_mut(&mut self) -> &mut StyleBookkeeping {
+        &mut self.bookkeeping
+    }
+
+    fn mounted_classes(&self) -> Option<Vec<String>> {
+        let elements = self.document.query_selector_all(".sst-class").ok()?;
+        Some(element_classes(&elements))
+    }
+}
+
+fn element_classes(elements: &web_sys::NodeList) -> Vec<String> {
+    (0..elements.length())
+        .filter_map(|index| elements.item(index))
+        .filter_map(|node| node.dyn_into::<web_sys::Element>().ok())
+        .map(|element| element.class_name())
+        .collect()
+}
+
+// Inserts rules into a `<style>` element appended to a shadow root,
+// so that styles apply to the shadow tree of a web component.
+//
+// Global styles are keyed off the document's `<html>` class and do not reach into shadow trees,
+// the class is kept here only so it can be inspected.
+pub struct ShadowRootBackend {
+    shadow_root: web_sys::ShadowRoot,
+    dom: DomBackend,
+    html_class: Option<String>,
+}
+
+impl ShadowRootBackend {
+    pub fn new(shadow_root: &web_sys::ShadowRoot) -> ShadowRootBackend {
+        let style_elem = document()
+            .create_element("style")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlStyleElement>()
+            .unwrap();
+        let _ = shadow_root.append_child(&style_elem);
+
+        ShadowRootBackend {
+            shadow_root: shadow_root.clone(),
+            dom: DomBackend::with_style_element(style_elem),
+            html_class: None,
+        }
+    }
+}
+
+impl StyleSheetBackend for ShadowRootBackend {
+    fn rules_length(&self) -> u32 {
+        self.dom.rules_length()
+    }
+
+    fn insert_rule(&mut self, rule: &str, index: u32) {
+        self.dom.insert_rule(rule, index)
+    }
+
+    fn delete_rule(&mut self, index: u32) {
+        self.dom.delete_rule(index)
+    }
+
+    fn html_class(&self) -> Option<String> {
+        self.html_class.clone()
+    }
+
+    fn set_html_class(&mut self, class_name: &str) {
+        self.html_class = Some(class_name.to_string());
+    }
+
+    fn bookkeeping(&self) -> &StyleBookkeeping {
+        self.dom.bookkeeping()
+    }
+
+    fn bookkeeping_mut(&mut self) -> &mut StyleBookkeeping {
+        self.dom.bookkeeping_mut()
+    }
+
+    fn mounted_classes(&self) -> Option<Vec<String>> {
+        let elements = self.shadow_root.query_selector_all(".sst-class").ok()?;
+        Some(element_classes(&elements))
+    }
+}
+
+// Collects rules in memory in cascade order, no DOM access is needed at all.
+// This is what `use_css_registry()` installs.
+#[derive(Default, Clone, Debug)]
+pub struct InMemoryBackend {
+    pub rules: Vec<String>,
+    pub html_class: Option<String>,
+    mounted_classes: Option<Vec<String>>,
+    bookkeeping: StyleBookkeeping,
+}
+
+impl InMemoryBackend {
+    // Reports the class attributes of elements as mounted, standing in for a document,
+    // e.g. to test that `collect_unused_styles()` keeps the styles of skipped elements.
+    pub fn with_mounted_classes(mut self, classes: Vec<String>) -> InMemoryBackend {
+        self.mounted_classes = Some(classes);
+        self
+    }
+}
+
+impl StyleSheetBackend for InMemoryBackend {
+    fn rules_length(&self) -> u32 {
+        self.rules.len() as u32
+    }
+
+    fn insert_rule(&mut self, rule: &str, index: u32) {
+        let index = (index as usize).min(self.rules.len());
+        self.rules.insert(index, rule.to_string());
+    }
+
+    fn delete_rule(&mut self, index: u32) {
+        if (index as usize) < self.rules.len() {
+            self.rules.remove(index as usize);
+        }
+    }
+
+    fn html_class(&self) -> Option<String> {
+        self.html_class.clone()
+    }
+
+    fn set_html_class(&mut self, class_name: &str) {
+        self.html_class = Some(class_name.to_string());
+    }
+
+    fn bookkeeping(&self) -> &StyleBookkeeping {
+        &self.bookkeeping
+    }
+
+    fn bookkeeping_mut(&mut self) -> &mut StyleBookkeeping {
+        &mut self.bookkeeping
+    }
+
+    fn css_text(&self) -> Option<String> {
+        Some(self.rules.join("\n"))
+    }
+
+    fn mounted_classes(&self) -> Option<Vec<String>> {
+        self.mounted_classes.clone()
+    }
+}
+
+// Writes the stylesheet text to a shared string buffer.
+// Keep a clone of `buffer()` to read the css from outside, e.g. to stream it into a response
+// or to assert on it from a test double.
+#[derive(Default, Clone, Debug)]
+pub struct StringBufferBackend {
+    buffer: Rc<RefCell<String>>,
+    rules: Vec<String>,
+    html_class: Option<String>,
+    bookkeeping: StyleBookkeeping,
+}
+
+impl StringBufferBackend {
+    pub fn new() -> StringBufferBackend {
+        StringBufferBackend::default()
+    }
+
+    pub fn buffer(&self) -> Rc<RefCell<String>> {
+        self.buffer.clone()
+    }
+
+    fn write_buffer(&self) {
+        let mut buffer = self.buffer.borrow_mut();
+        buffer.clear();
+        for rule in &self.rules {
+            buffer.push_str(rule);
+            buffer.push_str("\n");
+        }
+    }
+}
+
+impl StyleSheetBackend for StringBufferBackend {
+    fn rules_length(&self) -> u32 {
+        self.rules.len() as u32
+    }
+
+    fn insert_rule(&mut self, rule: &str, index: u32) {
+        let index = (index as usize).min(self.rules.len());
+        self.rules.insert(index, rule.to_string());
+        self.write_buffer();
+    }
+
+    fn delete_rule(&mut self, index: u32) {
+        if (index as usize) < self.rules.len() {
+            self.rules.remove(index as usize);
+            self.write_buffer();
+        }
+    }
+
+    fn html_class(&self) -> Option<String> {
+        self.html_class.clone()
+    }
+
+    fn set_html_class(&mut self, class_name: &str) {
+        self.html_class = Some(class_name.to_string());
+    }
+
+    fn bookkeeping(&self) -> &StyleBookkeeping {
+        &self.bookkeeping
+    }
+
+    fn bookkeeping_mut(&mut self) -> &mut StyleBookkeeping {
+        &mut self.bookkeeping
+    }
+
+    fn css_text(&self) -> Option<String> {
+        Some(self.buffer.borrow().clone())
+    }
+}
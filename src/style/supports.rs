@@ -0,0 +1,149 @@
+use crate::style::CssValueTrait;
+
+// Feature queries.
+//
+// Conditions are built from declarations, typed css values or selectors and combined with
+// `and`, `or` and `!`:
+//
+// s().display_flex()
+//     .supports(CssDisplay::Grid)
+//     -> @supports (display: grid)
+// s().supports(supports_decl("backdrop-filter", "blur(4px)").or(supports_decl("-webkit-backdrop-filter", "blur(4px)")))
+//     -> @supports (backdrop-filter: blur(4px)) or (-webkit-backdrop-filter: blur(4px))
+// s().supports(!supports_selector(":has(a)"))
+//     -> @supports not selector(:has(a))
+//
+// Raw strings are still accepted, with or without the `@supports` prefix.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SupportsCondition {
+    // `property: value`
+    Declaration(String),
+    // a selector for `selector(..)`
+    Selector(String),
+    And(Vec<SupportsCondition>),
+    Or(Vec<SupportsCondition>),
+    Not(Box<SupportsCondition>),
+    // condition text passed as a string
+    Raw(String),
+}
+
+pub fn supports_decl(property: &str, value: &str) -> SupportsCondition {
+    SupportsCondition::Declaration(format!("{}: {}", property, value))
+}
+
+pub fn supports_selector(selector: &str) -> SupportsCondition {
+    SupportsCondition::Selector(selector.to_string())
+}
+
+impl SupportsCondition {
+    // Both conditions have to hold.
+    pub fn and<C: Into<SupportsCondition>>(self, other: C) -> SupportsCondition {
+        match (self, other.into()) {
+            (SupportsCondition::And(mut left), SupportsCondition::And(right)) => {
+                left.extend(right);
+                SupportsCondition::And(left)
+            }
+            (SupportsCondition::And(mut left), right) => {
+                left.push(right);
+                SupportsCondition::And(left)
+            }
+            (left, right) => SupportsCondition::And(vec![left, right]),
+        }
+    }
+
+    // Either condition has to hold.
+    pub fn or<C: Into<SupportsCondition>>(self, other: C) -> SupportsCondition {
+        match (self, other.into()) {
+            (SupportsCondition::Or(mut left), SupportsCondition::Or(right)) => {
+                left.extend(right);
+                SupportsCondition::Or(left)
+            }
+            (SupportsCondition::Or(mut left), right) => {
+                left.push(right);
+                SupportsCondition::Or(left)
+            }
+            (left, right) => SupportsCondition::Or(vec![left, right]),
+        }
+    }
+
+    // Also available as `!condition`.
+    pub fn negate(self) -> SupportsCondition {
+        match self {
+            SupportsCondition::Not(inner) => *inner,
+            condition => SupportsCondition::Not(Box::new(condition)),
+        }
+    }
+
+    // A condition that can appear as an operand of `and`, `or` and `not`.
+    fn render_operand(&self) -> String {
+        match self {
+            SupportsCondition::Declaration(_) | SupportsCondition::Selector(_) => self.to_string(),
+            condition => format!("({})", condition),
+        }
+    }
+
+    // The full at rule prelude, e.g. `@supports (display: grid)`.
+    pub fn render(&self) -> String {
+        format!("@supports {}", self)
+    }
+}
+
+impl std::fmt::Display for SupportsCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |conditions: &[SupportsCondition], operator: &str| {
+            conditions
+                .iter()
+                .map(|condition| condition.render_operand())
+                .collect::<Vec<_>>()
+                .join(operator)
+        };
+        match self {
+            SupportsCondition::Declaration(declaration) => write!(f, "({})", declaration),
+            SupportsCondition::Selector(selector) => write!(f, "selector({})", selector),
+            SupportsCondition::And(conditions) => write!(f, "{}", join(conditions, " and ")),
+            SupportsCondition::Or(conditions) => write!(f, "{}", join(conditions, " or ")),
+            SupportsCondition::Not(condition) => write!(f, "not {}", condition.render_operand()),
+            SupportsCondition::Raw(raw) => write!(f, "{}", raw),
+        }
+    }
+}
+
+impl std::ops::Not for SupportsCondition {
+    type Output = SupportsCondition;
+
+    fn not(self) -> SupportsCondition {
+        self.negate()
+    }
+}
+
+// A typed value such as `CssDisplay::Grid` tests its own declaration.
+impl<T: CssValueTrait> From<T> for SupportsCondition {
+    fn from(value: T) -> SupportsCondition {
+        let declaration = value.to_string();
+        SupportsCondition::Declaration(declaration.trim().trim_end_matches(';').to_string())
+    }
+}
+
+impl From<&str> for SupportsCondition {
+    fn from(condition: &str) -> SupportsCondition {
+        let condition = condition.trim();
+        let condition = condition
+            .strip_prefix("@supports")
+            .unwrap_or(condition)
+            .trim();
+        SupportsCondition::Raw(condition.to_string())
+    }
+}
+
+impl From<String> for SupportsCondition {
+    fn from(condition: String) -> SupportsCondition {
+        SupportsCondition::from(condition.as_str())
+    }
+}
+
+impl From<&SupportsCondition> for SupportsCondition {
+    fn from(condition: &SupportsCondition) -> SupportsCondition {
+        condition.clone()
+    }
+}
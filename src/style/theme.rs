@@ -1,14 +1,20 @@
 use crate::style::css_values::*;
 use crate::style::ReturnBpScale;
 use crate::style::ReturnBpTuple;
-use crate::style::{CssValueTrait, Rule, Style, UpdateStyle};
+use crate::style::stylesheet::replace_pinned_rule;
+use crate::style::theme_validation::*;
+use crate::style::breakpoints::*;
+use crate::style::media_query::*;
+use crate::style::{CssValueTrait, Style, UpdateStyle};
 use anymap::any::Any;
 use seed::{prelude::*, *};
 use seed_hooks::*;
 use seed_style_macros::generate_froms;
 use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::any::TypeId;
 use std::marker::PhantomData;
 pub trait BorderTheme: Eq + Hash + Clone {}
 pub trait BorderWidthTheme: Eq + Hash + Clone {}
@@ -28,19 +34,80 @@ pub trait ShadowTheme: Eq + Hash + Clone {}
 pub trait StyleTheme: Eq + Hash + Clone {}
 pub trait BreakpointTheme: Eq + Hash + Clone {}
 
+// Implemented by `#[derive(ThemeAlias)]` for alias enums.
+pub trait ThemeAlias: Sized + Eq + Hash + Clone + 'static {
+    fn variants() -> Vec<Self>;
+
+    // the variant name, e.g. "Primary"
+    fn alias_name(&self) -> &'static str;
+
+    // the kinds listed in `#[theme(..)]`, e.g. ["color"]
+    fn theme_kinds() -> Vec<&'static str>;
+
+    // sets the `#[theme(.., value = "..")]` value of each variant
+    fn register_defaults(theme: Theme) -> Theme;
+}
+
 thread_local! {
     static THEMES_VEC : RefCell<Vec<Theme>> = RefCell::new(vec![]);
+    // names of the scoped themes provided to the view currently being built, outermost first.
+    static THEME_PROVIDERS : RefCell<Vec<String>> = RefCell::new(vec![]);
+}
+
+
+// Either a full replacement theme or a partial patch:
+//
+// change_theme_with_name("dark", dark_theme());
+// change_theme_with_name("dark", |theme: Theme| theme.set_color(Brand::Primary, hsl(200, 70, 50)));
+// change_theme_with_name("dark", ThemePatch(Theme::new("").set_color(Brand::Primary, hsl(200, 70, 50))));
+pub trait ThemeChange {
+    fn apply_to(self, existing: Theme) -> Theme;
+}
+
+impl ThemeChange for Theme {
+    fn apply_to(self, _existing: Theme) -> Theme {
+        self
+    }
 }
 
+// Overrides only the scales and alias entries it sets.
+pub struct ThemePatch(pub Theme);
 
-pub fn change_theme_with_name(name: &str, theme: Theme) {
-    app_themes().update( |v|
+impl ThemeChange for ThemePatch {
+    fn apply_to(self, existing: Theme) -> Theme {
+        existing.with_overrides(self.0)
+    }
+}
+
+impl<F> ThemeChange for F
+where
+    F: FnOnce(Theme) -> Theme,
+{
+    fn apply_to(self, existing: Theme) -> Theme {
+        self(existing)
+    }
+}
+
+pub fn change_theme_with_name<C: ThemeChange>(name: &str, change: C) {
+    let themes = if app_themes().observe_with(|v| v.iter().any(|t| &t.name == name)) {
+        app_themes()
+    } else {
+        scoped_themes()
+    };
+
+    themes.update( |v|
         if let Some(existing_theme) = v.iter_mut().find(|t| &t.name == name) {
-            let _old_theme = std::mem::replace(existing_theme, theme);
+            let old_owner = existing_theme.css_vars_owner();
+            let theme = change.apply_to(std::mem::take(existing_theme));
+            if let Some(block) = theme.render_css_vars() {
+                replace_pinned_rule(old_owner, theme.css_vars_owner(), &block);
+            }
+            *existing_theme = theme;
         } else {
             panic!("old theme doesnt exist");
         }
-    )
+    );
+    start_breakpoint_service();
 }
 
 impl From<CssSize> for CssWidth {
@@ -1308,12 +1375,7 @@ where
     let bp_pair = with_themes(ReturnBpTuple(bp));
     match bp_pair {
         (_lower, Some(higher)) => {
-            if window()
-                .match_media(&format!("(max-width: {}px)", higher))
-                .unwrap()
-                .unwrap()
-                .matches()
-            {
+            if viewport_at_most(higher) {
                 content()
             } else {
                 empty![]
@@ -1326,25 +1388,8 @@ where
 pub fn at_breakpoint_and_above<T>(bp: T) -> bool 
 where
     T: BreakpointTheme + 'static,{
-    let bp_pair = with_themes(ReturnBpTuple(bp));
-    match bp_pair {
-        (lower, Some(_higher)) => {
-            window()
-                .match_media(&format!("(min-width: {}px)", lower))
-                .unwrap()
-                .unwrap()
-                .matches()
-          
-        }
-        (lower, None) => {
-            window()
-                .match_media(&format!("(min-width: {}px)", lower))
-                .unwrap()
-                .unwrap()
-                .matches()
-          
-        }
-    }
+    let (lower, _higher) = with_themes(ReturnBpTuple(bp));
+    viewport_at_least(lower)
 }
 
 pub fn only_and_above<T, F, Ms>(bp: T, content: F) -> Node<Ms>
@@ -1352,32 +1397,11 @@ where
     T: BreakpointTheme + 'static,
     F: FnOnce() -> Node<Ms>,
 {
-    let bp_pair = with_themes(ReturnBpTuple(bp));
-    match bp_pair {
-        (lower, Some(_higher)) => {
-            if window()
-                .match_media(&format!("(min-width: {}px)", lower))
-                .unwrap()
-                .unwrap()
-                .matches()
-            {
-                content()
-            } else {
-                empty![]
-            }
-        }
-        (lower, None) => {
-            if window()
-                .match_media(&format!("(min-width: {}px)", lower))
-                .unwrap()
-                .unwrap()
-                .matches()
-            {
-                content()
-            } else {
-                empty![]
-            }
-        }
+    let (lower, _higher) = with_themes(ReturnBpTuple(bp));
+    if viewport_at_least(lower) {
+        content()
+    } else {
+        empty![]
     }
 }
 
@@ -1387,34 +1411,28 @@ where
     F: FnOnce() -> Node<Ms>,
 {
     let bp_pair = with_themes(ReturnBpTuple(bp));
-    match bp_pair {
-        (lower, Some(higher)) => {
-            if window()
-                .match_media(&format!(
-                    "(min-width: {}px) and (max-width: {}px)",
-                    lower, higher
-                ))
-                .unwrap()
-                .unwrap()
-                .matches()
-            {
-                content()
-            } else {
-                empty![]
-            }
-        }
-        (lower, None) => {
-            if window()
-                .match_media(&format!("(min-width: {}px)", lower))
-                .unwrap()
-                .unwrap()
-                .matches()
-            {
-                content()
-            } else {
-                empty![]
-            }
-        }
+    let is_inside = match bp_pair {
+        (lower, Some(higher)) => viewport_at_least(lower) && viewport_at_most(higher),
+        (lower, None) => viewport_at_least(lower),
+    };
+    if is_inside {
+        content()
+    } else {
+        empty![]
+    }
+}
+
+// Renders `content` while an arbitrary media query matches, e.g. `when_media(media().print(), || ..)`.
+// Unlike the breakpoint helpers this asks the browser on each render.
+pub fn when_media<Q, F, Ms>(query: Q, content: F) -> Node<Ms>
+where
+    Q: Into<MediaQuery>,
+    F: FnOnce() -> Node<Ms>,
+{
+    if media_query_matches(query) {
+        content()
+    } else {
+        empty![]
     }
 }
 
@@ -1427,20 +1445,7 @@ where
 
     match bp_pair {
         (lower, Some(higher)) => {
-            if window()
-                .match_media(&format!(
-                    "(max-width: {}px) and (max-width: {}px)",
-                    lower, higher
-                ))
-                .unwrap()
-                .unwrap()
-                .matches()
-                || window()
-                    .match_media(&format!("(min-width: {}px)", higher))
-                    .unwrap()
-                    .unwrap()
-                    .matches()
-            {
+            if !viewport_at_least(lower) || !viewport_at_most(higher) {
                 content()
             } else {
                 empty![]
@@ -1483,19 +1488,70 @@ pub fn app_themes() -> Atom<Vec<Theme>> {
     vec![]
 }
 
+// Themes that only apply inside a `with_theme_provider` subtree.
+#[atom]
+pub fn scoped_themes() -> Atom<Vec<Theme>> {
+    vec![]
+}
+
+// App themes come first, then the themes of any enclosing providers, so the nearest provider
+// is the first theme reached when iterating in reverse.
 pub fn with_themes<Q, R>(with: Q) -> R
 where
     Q: ActOnIteratorOfThemes<R>,
 {
-    app_themes().observe_with (|v|    
-        with.call(v.iter())
-    )
+    let providers = THEME_PROVIDERS.with(|p| p.borrow().clone());
+
+    if providers.is_empty() {
+        return app_themes().observe_with(|v| with.call(v.iter()));
+    }
+
+    app_themes().observe_with(|app| {
+        scoped_themes().observe_with(|scoped| {
+            let provided = providers
+                .iter()
+                .filter_map(|name| scoped.iter().find(|t| &t.name == name));
+            with.call(app.iter().chain(provided))
+        })
+    })
+}
+
+// Applies a scoped theme to everything built inside `content`, e.g. a dark sidebar in a light app.
+// Aliases resolve against the nearest provider first and fall back to the app themes.
+//
+// with_theme_provider("sidebar_dark", || sidebar_view(model))
+pub fn with_theme_provider<F, R>(name: &str, content: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    THEME_PROVIDERS.with(|p| p.borrow_mut().push(name.to_string()));
+    let result = content();
+    THEME_PROVIDERS.with(|p| p.borrow_mut().pop());
+    result
 }
 
 
 pub fn load_app_themes(themes:&[fn()->Theme]) {
     for theme in themes {
-        app_themes().update(|t| t.push(theme()))
+        load_app_theme(theme());
+    }
+}
+
+// Loads a theme that was built at runtime, e.g. parsed from a fetched theme file.
+pub fn load_app_theme(theme: Theme) {
+    activate_theme_css_vars(&theme);
+    let has_breakpoints = !theme.media_bp_pairs.is_empty();
+    app_themes().update(|t| t.push(theme));
+    if has_breakpoints {
+        start_breakpoint_service();
+    }
+}
+
+pub fn load_scoped_themes(themes:&[fn()->Theme]) {
+    for theme in themes {
+        let theme = theme();
+        activate_theme_css_vars(&theme);
+        scoped_themes().update(|t| t.push(theme))
     }
 }
 
@@ -1519,6 +1575,9 @@ pub struct Theme {
     pub radii_scale: Vec<CssBorderRadius>,
     pub colors_scale: Vec<CssColor>,
     pub shadows_scale: Vec<CssShadow>,
+    pub css_vars_selector: Option<String>,
+    pub css_var_aliases: Vec<CssVarCollector>,
+    pub alias_maps: Vec<AliasMapMerger>,
 }
 
 impl Default for Theme {
@@ -1542,10 +1601,75 @@ impl Default for Theme {
             colors_scale: vec![],
             shadows_scale: vec![],
             radii_scale: vec![],
+            css_vars_selector: None,
+            css_var_aliases: vec![],
+            alias_maps: vec![],
         }
     }
 }
 
+// Theme inheritance.
+//
+// A theme can extend a base theme, overriding some scales and alias entries and inheriting the rest:
+//
+// fn dark_theme() -> Theme {
+//     Theme::new("dark")
+//         .extend(&light_theme())
+//         .set_color(Brand::Background, hsl(0, 0, 10))
+// }
+//
+// A scale is inherited as a whole if the child does not set it, alias entries are inherited one by one.
+// `base.with_overrides(patch)` is the same merge with the base named first.
+//
+// Themes are merged when they are built, lookups are unaffected.
+
+// Copies the entries of one alias map, `HashMap<Q, V>`, that the child theme does not define.
+// Keyed by the type of the anymap entry it copies.
+#[derive(Clone, Copy)]
+pub struct AliasMapMerger(TypeId, fn(&Theme, &mut Theme));
+
+impl AliasMapMerger {
+    fn new<K: 'static>(merge: fn(&Theme, &mut Theme)) -> AliasMapMerger {
+        AliasMapMerger(TypeId::of::<K>(), merge)
+    }
+}
+
+impl std::fmt::Debug for AliasMapMerger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AliasMapMerger")
+    }
+}
+
+impl PartialEq for AliasMapMerger {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+fn merge_alias_map<Q, V>(base: &Theme, child: &mut Theme)
+where
+    Q: 'static + Eq + Hash + Clone,
+    V: 'static + Clone,
+{
+    if let Some(base_hm) = base.anymap.get::<HashMap<Q, V>>() {
+        for (alias, value) in base_hm {
+            let has_alias = child
+                .anymap
+                .get::<HashMap<Q, V>>()
+                .map_or(false, |hm| hm.contains_key(alias));
+            if !has_alias {
+                child.insert_alias_value(alias.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn inherit_scale<T: Clone>(child: &mut Vec<T>, base: &[T]) {
+    if child.is_empty() {
+        *child = base.to_vec();
+    }
+}
+
 pub trait OverloadedStyleLookUp<T, R> {
     fn overloaded_lookup(&self, alias: T) -> Option<R>;
 }
@@ -1691,6 +1815,74 @@ impl Theme {
         }
     }
 
+    pub(crate) fn insert_alias_value<Q, V>(&mut self, alias: Q, value: V)
+    where
+        Q: 'static + Eq + Hash + Clone,
+        V: 'static + Clone,
+    {
+        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, V>>() {
+            hm.insert(alias, value);
+        } else {
+            let mut hm = HashMap::<Q, V>::new();
+            hm.insert(alias, value);
+            self.anymap.insert(hm);
+            self.alias_maps.push(AliasMapMerger::new::<HashMap<Q, V>>(
+                merge_alias_map::<Q, V>,
+            ));
+        }
+    }
+
+    // Inherits every scale and alias entry of `base` that this theme does not set.
+    pub fn extend(mut self, base: &Theme) -> Theme {
+        inherit_scale(&mut self.spaces_scale, &base.spaces_scale);
+        inherit_scale(&mut self.font_sizes_scale, &base.font_sizes_scale);
+        inherit_scale(&mut self.fonts_scale, &base.fonts_scale);
+        inherit_scale(&mut self.font_weights_scale, &base.font_weights_scale);
+        inherit_scale(&mut self.line_heights_scale, &base.line_heights_scale);
+        inherit_scale(&mut self.letter_spacings_scale, &base.letter_spacings_scale);
+        inherit_scale(&mut self.sizes_scale, &base.sizes_scale);
+        inherit_scale(&mut self.borders_scale, &base.borders_scale);
+        inherit_scale(&mut self.border_styles_scale, &base.border_styles_scale);
+        inherit_scale(&mut self.border_widths_scale, &base.border_widths_scale);
+        inherit_scale(&mut self.radii_scale, &base.radii_scale);
+        inherit_scale(&mut self.colors_scale, &base.colors_scale);
+        inherit_scale(&mut self.shadows_scale, &base.shadows_scale);
+        // the media queries are derived from the breakpoints, so they are inherited together
+        if self.breakpoints_scale.is_empty() {
+            self.breakpoints_scale = base.breakpoints_scale.clone();
+            self.media_bp_scale = base.media_bp_scale.clone();
+            self.media_bp_pairs = base.media_bp_pairs.clone();
+        }
+
+        for AliasMapMerger(_, merge) in &base.alias_maps {
+            merge(base, &mut self);
+        }
+
+        if self.css_vars_selector.is_none() {
+            self.css_vars_selector = base.css_vars_selector.clone();
+        }
+        for collector in &base.css_var_aliases {
+            if !self.css_var_aliases.contains(collector) {
+                self.css_var_aliases.push(*collector);
+            }
+        }
+        self
+    }
+
+    // Applies the scales and alias entries set in `overrides`, keeping everything else and the name of this theme.
+    pub fn with_overrides(self, overrides: Theme) -> Theme {
+        let name = self.name.clone();
+        Theme {
+            name,
+            ..overrides.extend(&self)
+        }
+    }
+
+    pub fn with_alias_defaults<Q: ThemeAlias>(self) -> Theme {
+        register_alias_names::<Q>();
+        Q::register_defaults(self)
+    }
+
     pub fn space_scale<S>(mut self, scale: &[S]) -> Theme
     where
         S: Into<CssSpace> + Clone,
@@ -1769,13 +1961,7 @@ impl Theme {
 
         self.media_bp_scale = bp_pairs
             .iter()
-            .map(|pair| match pair {
-                (lower, Some(higher)) => CssMedia(format!(
-                    "@media (min-width: {}px) and (max-width: {}px)",
-                    lower, higher
-                )),
-                (lower, None) => CssMedia(format!("@media (min-width: {}px)", lower)),
-            })
+            .map(|pair| pair_media_query(*pair).into())
             .collect::<Vec<CssMedia>>();
 
         self.media_bp_pairs = bp_pairs;
@@ -1810,13 +1996,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssSize>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssSize>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1827,13 +2007,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssShadow>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssShadow>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1843,13 +2017,7 @@ impl Theme {
         Q: 'static + ColorTheme,
     {
         let value = value.into();
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssColor>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssColor>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1860,13 +2028,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssSpace>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssSpace>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1876,13 +2038,7 @@ impl Theme {
         Q: 'static + FontSizeTheme,
     {
         let value = value.into();
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssFontSize>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssFontSize>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1893,13 +2049,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssBorder>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssBorder>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1910,13 +2060,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssBorderWidth>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssBorderWidth>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1927,13 +2071,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssBorderStyle>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssBorderStyle>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1944,13 +2082,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssBorderRadius>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssBorderRadius>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1961,13 +2093,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssTransition>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssTransition>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1978,13 +2104,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, Style>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, Style>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -1992,13 +2112,7 @@ impl Theme {
     where
         Q: 'static + BreakpointTheme,
     {
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, (u32, Option<u32>)>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, (u32, Option<u32>)>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -2009,13 +2123,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssLineHeight>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssLineHeight>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 
@@ -2026,13 +2134,7 @@ impl Theme {
     {
         let value = value.into();
 
-        if let Some(hm) = self.anymap.get_mut::<HashMap<Q, CssLetterSpacing>>() {
-            hm.insert(alias, value);
-        } else {
-            let mut hm = HashMap::<Q, CssLetterSpacing>::new();
-            hm.insert(alias, value);
-            self.anymap.insert(hm);
-        }
+        self.insert_alias_value(alias, value);
         self
     }
 }
@@ -2058,7 +2160,279 @@ where
     where
         It: DoubleEndedIterator<Item = &'a Theme>,
     {
-        it.find_map(|theme| theme.get::<T, Style>(self.0.clone()))
-            .expect("Cannot find a theme that defines that CSS Value, are you sure you have provided access to that theme using `use_themes(|| THEME_NAME, ||..`")
+        // the last theme wins, as for every other alias lookup, so provided scoped themes
+        // override the app themes
+        it.rev().find_map(|theme| theme.get::<T, Style>(self.0.clone()))
+            .unwrap_or_else(|| missing_theme_value::<T, Style>(&self.0))
+    }
+}
+
+// Css variable mode.
+//
+// A theme built with `.css_vars(":root")`, or with a scoping class selector such as `".dark"`,
+// is emitted once as a block of custom properties when it is loaded. Scale lookups then render
+// as `var(--space-2)` and lookups of aliases registered with `.css_var_alias::<Q>()` render as
+// `var(--color-primary)`. Switching themes only swaps the variable block and existing classes are reused.
+//
+// Alias variable names are the kebab-cased `Debug` output of the alias.
+
+pub trait ThemeCssVar: for<'a> From<&'a str> + std::fmt::Display {
+    fn css_var_prefix() -> &'static str;
+}
+
+impl ThemeCssVar for CssColor {
+    fn css_var_prefix() -> &'static str {
+        "color"
+    }
+}
+
+impl ThemeCssVar for CssSpace {
+    fn css_var_prefix() -> &'static str {
+        "space"
+    }
+}
+
+impl ThemeCssVar for CssSize {
+    fn css_var_prefix() -> &'static str {
+        "size"
+    }
+}
+
+impl ThemeCssVar for CssFontSize {
+    fn css_var_prefix() -> &'static str {
+        "font-size"
+    }
+}
+
+impl ThemeCssVar for CssShadow {
+    fn css_var_prefix() -> &'static str {
+        "shadow"
+    }
+}
+
+impl ThemeCssVar for CssBorder {
+    fn css_var_prefix() -> &'static str {
+        "border"
+    }
+}
+
+impl ThemeCssVar for CssBorderWidth {
+    fn css_var_prefix() -> &'static str {
+        "border-width"
+    }
+}
+
+impl ThemeCssVar for CssBorderStyle {
+    fn css_var_prefix() -> &'static str {
+        "border-style"
+    }
+}
+
+impl ThemeCssVar for CssBorderRadius {
+    fn css_var_prefix() -> &'static str {
+        "radius"
+    }
+}
+
+// Collects the variable declarations of one registered alias type, keyed by that type.
+#[derive(Clone, Copy)]
+pub struct CssVarCollector(TypeId, fn(&Theme) -> Vec<(String, String)>);
+
+impl std::fmt::Debug for CssVarCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CssVarCollector")
+    }
+}
+
+impl PartialEq for CssVarCollector {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+// Stored in the anymap so that lookups can name an alias without a `Debug` bound.
+struct CssVarAliasName<Q>(fn(&Q) -> String);
+
+fn inherit_css_var_alias_name<Q: 'static + std::fmt::Debug>(_base: &Theme, child: &mut Theme) {
+    if child.anymap.get::<CssVarAliasName<Q>>().is_none() {
+        child.register_css_var_alias_name::<Q>();
+    }
+}
+
+fn css_var_alias_name<Q: std::fmt::Debug>(alias: &Q) -> String {
+    kebab_case_name(&format!("{:?}", alias))
+}
+
+// e.g. `DarkBlue` -> `dark-blue`, `Gray(3)` -> `gray-3`, `color.brand` -> `color-brand`
+pub(crate) fn kebab_case_name(text: &str) -> String {
+    let mut name = String::new();
+    let mut after_lower = false;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && after_lower {
+                name.push('-');
+            }
+            after_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            name.push(c.to_ascii_lowercase());
+        } else {
+            if !name.is_empty() && !name.ends_with('-') {
+                name.push('-');
+            }
+            after_lower = false;
+        }
+    }
+    name.trim_end_matches('-').to_string()
+}
+
+// Theme values display as full declarations, e.g. `color: #fff;`
+pub(crate) fn css_var_declaration_value<V: std::fmt::Display>(value: &V) -> String {
+    let declaration = value.to_string();
+    declaration
+        .splitn(2, ':')
+        .nth(1)
+        .unwrap_or(&declaration)
+        .trim()
+        .trim_end_matches(';')
+        .trim()
+        .to_string()
+}
+
+fn collect_alias_css_vars<Q>(theme: &Theme) -> Vec<(String, String)>
+where
+    Q: 'static + Eq + Hash + Clone + std::fmt::Debug,
+{
+    let mut vars = vec![];
+    theme.push_alias_css_vars::<Q, CssColor>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssSpace>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssSize>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssFontSize>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssShadow>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssBorder>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssBorderWidth>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssBorderStyle>(&mut vars);
+    theme.push_alias_css_vars::<Q, CssBorderRadius>(&mut vars);
+    vars
+}
+
+fn push_scale_css_vars<V: ThemeCssVar>(scale: &[V], vars: &mut Vec<(String, String)>) {
+    for (idx, value) in scale.iter().enumerate() {
+        vars.push((
+            format!("--{}-{}", V::css_var_prefix(), idx),
+            css_var_declaration_value(value),
+        ));
+    }
+}
+
+// Emits the variable block of a theme in css variable mode, or swaps it if already emitted.
+// Themes passed to `load_app_themes` and `change_theme_with_name` are activated automatically.
+pub fn activate_theme_css_vars(theme: &Theme) {
+    if let Some(block) = theme.render_css_vars() {
+        let owner = theme.css_vars_owner();
+        replace_pinned_rule(owner, owner, &block);
+    }
+}
+
+impl Theme {
+    pub fn css_vars(mut self, selector: &str) -> Theme {
+        self.css_vars_selector = Some(selector.to_string());
+        self
+    }
+
+    pub fn css_var_alias<Q>(mut self) -> Theme
+    where
+        Q: 'static + Eq + Hash + Clone + std::fmt::Debug,
+    {
+        self.register_css_var_alias_name::<Q>();
+        let collector = CssVarCollector(TypeId::of::<Q>(), collect_alias_css_vars::<Q>);
+        if !self.css_var_aliases.contains(&collector) {
+            self.css_var_aliases.push(collector);
+        }
+        self
+    }
+
+    // also pushes the merger so that themes extending this one keep the names
+    fn register_css_var_alias_name<Q: 'static + std::fmt::Debug>(&mut self) {
+        self.anymap
+            .insert(CssVarAliasName::<Q>(css_var_alias_name::<Q>));
+        let merger = AliasMapMerger::new::<CssVarAliasName<Q>>(inherit_css_var_alias_name::<Q>);
+        if !self.alias_maps.contains(&merger) {
+            self.alias_maps.push(merger);
+        }
+    }
+
+    // All custom property declarations of this theme, sorted by name.
+    pub fn css_var_declarations(&self) -> Vec<(String, String)> {
+        let mut vars = vec![];
+        push_scale_css_vars(&self.colors_scale, &mut vars);
+        push_scale_css_vars(&self.spaces_scale, &mut vars);
+        push_scale_css_vars(&self.sizes_scale, &mut vars);
+        push_scale_css_vars(&self.font_sizes_scale, &mut vars);
+        push_scale_css_vars(&self.shadows_scale, &mut vars);
+        push_scale_css_vars(&self.borders_scale, &mut vars);
+        push_scale_css_vars(&self.border_widths_scale, &mut vars);
+        push_scale_css_vars(&self.border_styles_scale, &mut vars);
+        push_scale_css_vars(&self.radii_scale, &mut vars);
+        for CssVarCollector(_, collect) in &self.css_var_aliases {
+            vars.extend(collect(self));
+        }
+        vars.sort();
+        vars
+    }
+
+    // `:root { --color-primary: #fff; ... }`, `None` unless css variable mode is on.
+    pub fn render_css_vars(&self) -> Option<String> {
+        let selector = self.css_vars_selector.as_ref()?;
+        let declarations = self
+            .css_var_declarations()
+            .iter()
+            .map(|(name, value)| format!("{}: {};", name, value))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!("{} {{\n{}\n}}", selector, declarations))
+    }
+
+    fn push_alias_css_vars<Q, V>(&self, vars: &mut Vec<(String, String)>)
+    where
+        Q: 'static + Eq + Hash + Clone + std::fmt::Debug,
+        V: 'static + ThemeCssVar,
+    {
+        if let Some(hm) = self.anymap.get::<HashMap<Q, V>>() {
+            for (alias, value) in hm {
+                vars.push((
+                    format!("--{}-{}", V::css_var_prefix(), css_var_alias_name(alias)),
+                    css_var_declaration_value(value),
+                ));
+            }
+        }
+    }
+
+    fn css_vars_owner(&self) -> u64 {
+        let mut s = DefaultHasher::new();
+        ("theme-css-vars", &self.name, &self.css_vars_selector).hash(&mut s);
+        s.finish()
+    }
+
+    // used by lookups, returns `var(--color-primary)` in place of the value if the alias is registered
+    pub(crate) fn alias_value_or_css_var<Q: 'static, V: ThemeCssVar>(
+        &self,
+        alias: &Q,
+        value: V,
+    ) -> V {
+        if self.css_vars_selector.is_some() {
+            if let Some(CssVarAliasName(name)) = self.anymap.get::<CssVarAliasName<Q>>() {
+                let reference = var(&format!("{}-{}", V::css_var_prefix(), name(alias)));
+                return V::from(reference.to_string().as_str());
+            }
+        }
+        value
+    }
+
+    pub(crate) fn scale_value_or_css_var<V: ThemeCssVar>(&self, idx: usize, value: V) -> V {
+        if self.css_vars_selector.is_some() {
+            let reference = var(&format!("{}-{}", V::css_var_prefix(), idx));
+            V::from(reference.to_string().as_str())
+        } else {
+            value
+        }
     }
 }
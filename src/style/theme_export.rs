@@ -0,0 +1,295 @@
+use crate::style::theme::*;
+use crate::style::theme_files::*;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+// Exports of a theme for the non Rust parts of a product, e.g. marketing pages and emails.
+//
+// Scale entries are keyed by their index and alias entries by the key they were registered
+// with in `ThemeAliases`. So with `.color("color.primary", Brand::Primary)`:
+//
+// css          `:root { --color-0: #fff; --color-primary: #ff0000; }`
+// scss         `$colors: ("0": #fff, "color.primary": #ff0000);`
+// typescript   `export const theme = { "colors": { "0": "#fff", ... } } as const;`
+// json         `{ "color": { "$type": "color", "primary": { "$value": "#ff0000" } } }`
+//
+// Css variable names are built from these keys, `--{prefix}-{kebab-cased key}`. Css variable
+// mode names alias variables after the alias itself, so the two only agree when the key
+// kebab-cases to the alias name, e.g. `"primary"` or `"color.primary"` for `Brand::Primary`.
+//
+// The json tokens can be read back with `Theme::from_design_tokens`. Dotted keys nest, so keys
+// where one is a prefix of another, such as a `0.5` alias next to the scale index `0`, cannot be
+// exported as design tokens.
+
+struct ExportGroup {
+    // scss/typescript name
+    name: &'static str,
+    // css variable prefix
+    prefix: &'static str,
+    // design tokens group and `$type`
+    token_group: &'static str,
+    token_type: Option<&'static str>,
+    entries: Vec<(String, String)>,
+}
+
+fn export_group(
+    name: &'static str,
+    prefix: &'static str,
+    token_group: &'static str,
+    token_type: Option<&'static str>,
+    scale: &[String],
+    aliases: &BTreeMap<String, String>,
+) -> ExportGroup {
+    let mut entries = scale
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| (idx.to_string(), value.clone()))
+        .collect::<Vec<_>>();
+    entries.extend(aliases.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    ExportGroup {
+        name,
+        prefix,
+        token_group,
+        token_type,
+        entries,
+    }
+}
+
+fn export_groups(file: &ThemeFile) -> Vec<ExportGroup> {
+    let scales = &file.scales;
+    let aliases = &file.aliases;
+    let none = BTreeMap::new();
+    let breakpoints = scales
+        .breakpoints
+        .iter()
+        .map(|bp| format!("{}px", bp))
+        .collect::<Vec<_>>();
+
+    vec![
+        export_group(
+            "colors",
+            "color",
+            "color",
+            Some("color"),
+            &scales.colors,
+            &aliases.colors,
+        ),
+        export_group(
+            "spaces",
+            "space",
+            "space",
+            Some("dimension"),
+            &scales.spaces,
+            &aliases.spaces,
+        ),
+        export_group(
+            "sizes",
+            "size",
+            "size",
+            Some("dimension"),
+            &scales.sizes,
+            &aliases.sizes,
+        ),
+        export_group(
+            "font_sizes",
+            "font-size",
+            "fontSize",
+            Some("dimension"),
+            &scales.font_sizes,
+            &aliases.font_sizes,
+        ),
+        export_group("fonts", "font", "font", None, &scales.fonts, &none),
+        export_group(
+            "font_weights",
+            "font-weight",
+            "fontWeight",
+            Some("fontWeight"),
+            &scales.font_weights,
+            &none,
+        ),
+        export_group(
+            "line_heights",
+            "line-height",
+            "lineHeight",
+            None,
+            &scales.line_heights,
+            &aliases.line_heights,
+        ),
+        export_group(
+            "letter_spacings",
+            "letter-spacing",
+            "letterSpacing",
+            Some("dimension"),
+            &scales.letter_spacings,
+            &aliases.letter_spacings,
+        ),
+        export_group(
+            "borders",
+            "border",
+            "border",
+            None,
+            &scales.borders,
+            &aliases.borders,
+        ),
+        export_group(
+            "border_widths",
+            "border-width",
+            "borderWidth",
+            Some("dimension"),
+            &scales.border_widths,
+            &aliases.border_widths,
+        ),
+        export_group(
+            "border_styles",
+            "border-style",
+            "borderStyle",
+            None,
+            &scales.border_styles,
+            &aliases.border_styles,
+        ),
+        export_group(
+            "radii",
+            "radius",
+            "radius",
+            Some("dimension"),
+            &scales.radii,
+            &aliases.radii,
+        ),
+        export_group(
+            "shadows",
+            "shadow",
+            "shadow",
+            Some("shadow"),
+            &scales.shadows,
+            &aliases.shadows,
+        ),
+        export_group(
+            "transitions",
+            "transition",
+            "transition",
+            Some("transition"),
+            &[],
+            &aliases.transitions,
+        ),
+        export_group(
+            "breakpoints",
+            "breakpoint",
+            "breakpoint",
+            Some("dimension"),
+            &breakpoints,
+            &none,
+        ),
+    ]
+    .into_iter()
+    .filter(|group| !group.entries.is_empty())
+    .collect()
+}
+
+fn css_var_name(prefix: &str, key: &str) -> String {
+    let key = kebab_case_name(key);
+    let key = key
+        .strip_prefix(&format!("{}-", prefix))
+        .unwrap_or(&key)
+        .to_string();
+    format!("--{}-{}", prefix, key)
+}
+
+fn quoted(text: &str) -> String {
+    Value::String(text.to_string()).to_string()
+}
+
+impl Theme {
+    // A stylesheet with every scale entry and registered alias as a custom property.
+    pub fn export_css_vars(&self, aliases: &ThemeAliases, selector: &str) -> String {
+        let mut css = format!("{} {{\n", selector);
+        for group in export_groups(&ThemeFile::from_theme(self, aliases)) {
+            for (key, value) in &group.entries {
+                css.push_str(&format!(
+                    "  {}: {};\n",
+                    css_var_name(group.prefix, key),
+                    value
+                ));
+            }
+        }
+        css.push_str("}\n");
+        css
+    }
+
+    // One scss map per scale, e.g. `$colors`, `$spaces`.
+    pub fn export_scss(&self, aliases: &ThemeAliases) -> String {
+        export_groups(&ThemeFile::from_theme(self, aliases))
+            .iter()
+            .map(|group| {
+                let entries = group
+                    .entries
+                    .iter()
+                    .map(|(key, value)| format!("  {}: {}", quoted(key), value))
+                    .collect::<Vec<_>>()
+                    .join(",\n");
+                format!("${}: (\n{}\n);\n", group.name.replace('_', "-"), entries)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn export_typescript(&self, aliases: &ThemeAliases) -> String {
+        let mut theme = Map::new();
+        for group in export_groups(&ThemeFile::from_theme(self, aliases)) {
+            let entries = group
+                .entries
+                .into_iter()
+                .map(|(key, value)| (key, Value::String(value)))
+                .collect::<Map<_, _>>();
+            theme.insert(group.name.to_string(), Value::Object(entries));
+        }
+        let object = serde_json::to_string_pretty(&Value::Object(theme)).unwrap_or_default();
+        format!(
+            "export const theme = {} as const;\n\nexport default theme;\n",
+            object
+        )
+    }
+
+    // W3C design tokens json.
+    pub fn export_design_tokens(&self, aliases: &ThemeAliases) -> Result<String, ThemeFileError> {
+        let mut tokens = Map::new();
+        for group in export_groups(&ThemeFile::from_theme(self, aliases)) {
+            let mut group_tokens = Map::new();
+            if let Some(token_type) = group.token_type {
+                group_tokens.insert("$type".to_string(), Value::String(token_type.to_string()));
+            }
+
+            let token_group = group.token_group;
+            for (key, value) in group.entries {
+                // `color.brand.primary` nests as `brand.primary` inside the `color` group
+                let mut path = key.split('.').collect::<Vec<_>>();
+                if path.len() > 1 && path[0] == token_group {
+                    path.remove(0);
+                }
+                let collision =
+                    || ThemeFileError::DesignTokenCollision(format!("{}.{}", token_group, key));
+
+                let mut node = &mut group_tokens;
+                for segment in &path[..path.len() - 1] {
+                    node = match node
+                        .entry(segment.to_string())
+                        .or_insert_with(|| Value::Object(Map::new()))
+                    {
+                        Value::Object(child) if !child.contains_key("$value") => child,
+                        _ => return Err(collision()),
+                    };
+                }
+                let name = path[path.len() - 1].to_string();
+                if node.contains_key(&name) {
+                    return Err(collision());
+                }
+                let mut token = Map::new();
+                token.insert("$value".to_string(), Value::String(value));
+                node.insert(name, Value::Object(token));
+            }
+
+            tokens.insert(token_group.to_string(), Value::Object(group_tokens));
+        }
+        serde_json::to_string_pretty(&Value::Object(tokens)).map_err(ThemeFileError::Json)
+    }
+}
@@ -0,0 +1,582 @@
+use crate::style::css_values::*;
+use crate::style::theme::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Themes as data files.
+//
+// A `ThemeFile` is the serde form of a `Theme`. Scale values and alias values are plain css
+// strings, alias names are string keys. Because alias maps in a `Theme` are keyed by typed
+// enums, the string keys are mapped onto those enums through a `ThemeAliases` registration:
+//
+// fn brand_aliases() -> ThemeAliases {
+//     ThemeAliases::new()
+//         .color("primary", Brand::Primary)
+//         .space("gutter", Spacing::Gutter)
+// }
+//
+// fn brand_theme() -> Theme {
+//     Theme::from_json(include_str!("brand.json"), &brand_aliases()).unwrap()
+// }
+//
+// load_app_themes(&[brand_theme]);
+//
+// Themes fetched at runtime can be passed to `load_app_theme` instead.
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ThemeFile {
+    pub name: String,
+    #[serde(default)]
+    pub scales: ThemeFileScales,
+    #[serde(default)]
+    pub aliases: ThemeFileAliases,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct ThemeFileScales {
+    pub spaces: Vec<String>,
+    pub font_sizes: Vec<String>,
+    pub fonts: Vec<String>,
+    pub font_weights: Vec<String>,
+    pub line_heights: Vec<String>,
+    pub letter_spacings: Vec<String>,
+    pub sizes: Vec<String>,
+    pub borders: Vec<String>,
+    pub border_styles: Vec<String>,
+    pub border_widths: Vec<String>,
+    pub breakpoints: Vec<u32>,
+    pub radii: Vec<String>,
+    pub colors: Vec<String>,
+    pub shadows: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct ThemeFileAliases {
+    pub colors: BTreeMap<String, String>,
+    pub spaces: BTreeMap<String, String>,
+    pub sizes: BTreeMap<String, String>,
+    pub font_sizes: BTreeMap<String, String>,
+    pub shadows: BTreeMap<String, String>,
+    pub borders: BTreeMap<String, String>,
+    pub border_widths: BTreeMap<String, String>,
+    pub border_styles: BTreeMap<String, String>,
+    pub radii: BTreeMap<String, String>,
+    pub transitions: BTreeMap<String, String>,
+    pub line_heights: BTreeMap<String, String>,
+    pub letter_spacings: BTreeMap<String, String>,
+    pub breakpoints: BTreeMap<String, ThemeFileBreakpoint>,
+}
+
+// `max` is left out for the last, open ended, breakpoint.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+pub struct ThemeFileBreakpoint {
+    pub min: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum ThemeFileError {
+    Json(serde_json::Error),
+    TomlDe(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    // a dotted key that would nest a design token inside another token, e.g. `0.5` next to `0`
+    DesignTokenCollision(String),
+}
+
+impl std::fmt::Display for ThemeFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeFileError::Json(err) => write!(f, "invalid theme json: {}", err),
+            ThemeFileError::TomlDe(err) => write!(f, "invalid theme toml: {}", err),
+            ThemeFileError::TomlSer(err) => write!(f, "cannot write theme toml: {}", err),
+            ThemeFileError::DesignTokenCollision(path) => write!(
+                f,
+                "design token `{}` would be both a token and a token group",
+                path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ThemeFileError {}
+
+// One string key bound to a typed alias.
+struct AliasBinding<V> {
+    key: String,
+    set: Box<dyn Fn(Theme, V) -> Theme>,
+    get: Box<dyn Fn(&Theme) -> Option<V>>,
+}
+
+impl<V> AliasBinding<V> {
+    fn new<S, G>(key: &str, set: S, get: G) -> AliasBinding<V>
+    where
+        S: Fn(Theme, V) -> Theme + 'static,
+        G: Fn(&Theme) -> Option<V> + 'static,
+    {
+        AliasBinding {
+            key: key.to_string(),
+            set: Box::new(set),
+            get: Box::new(get),
+        }
+    }
+}
+
+// Maps the string keys used in theme files onto typed alias enums.
+#[derive(Default)]
+pub struct ThemeAliases {
+    colors: Vec<AliasBinding<String>>,
+    spaces: Vec<AliasBinding<String>>,
+    sizes: Vec<AliasBinding<String>>,
+    font_sizes: Vec<AliasBinding<String>>,
+    shadows: Vec<AliasBinding<String>>,
+    borders: Vec<AliasBinding<String>>,
+    border_widths: Vec<AliasBinding<String>>,
+    border_styles: Vec<AliasBinding<String>>,
+    radii: Vec<AliasBinding<String>>,
+    transitions: Vec<AliasBinding<String>>,
+    line_heights: Vec<AliasBinding<String>>,
+    letter_spacings: Vec<AliasBinding<String>>,
+    breakpoints: Vec<AliasBinding<ThemeFileBreakpoint>>,
+}
+
+impl ThemeAliases {
+    pub fn new() -> ThemeAliases {
+        ThemeAliases::default()
+    }
+
+    pub fn color<Q: 'static + ColorTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.colors.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_color(alias.clone(), CssColor::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssColor>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn space<Q: 'static + SpaceTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.spaces.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_space(alias.clone(), CssSpace::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssSpace>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn size<Q: 'static + SizeTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.sizes.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_size(alias.clone(), CssSize::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssSize>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn font_size<Q: 'static + FontSizeTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.font_sizes.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_font_size(alias.clone(), CssFontSize::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssFontSize>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn shadow<Q: 'static + ShadowTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.shadows.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_shadow(alias.clone(), CssShadow::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssShadow>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn border<Q: 'static + BorderTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.borders.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_border(alias.clone(), CssBorder::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssBorder>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn border_width<Q: 'static + BorderWidthTheme>(
+        mut self,
+        key: &str,
+        alias: Q,
+    ) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.border_widths.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_border_width(alias.clone(), CssBorderWidth::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssBorderWidth>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn border_style<Q: 'static + BorderStyleTheme>(
+        mut self,
+        key: &str,
+        alias: Q,
+    ) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.border_styles.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_border_style(alias.clone(), CssBorderStyle::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssBorderStyle>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn border_radius<Q: 'static + BorderRadiusTheme>(
+        mut self,
+        key: &str,
+        alias: Q,
+    ) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.radii.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_border_radius(alias.clone(), CssBorderRadius::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, CssBorderRadius>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn transition<Q: 'static + TransitionTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.transitions.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_transition(alias.clone(), CssTransition::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .general_get::<Q, CssTransition>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn line_height<Q: 'static + LineHeightTheme>(
+        mut self,
+        key: &str,
+        alias: Q,
+    ) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.line_heights.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_line_height(alias.clone(), CssLineHeight::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .general_get::<Q, CssLineHeight>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn letter_spacing<Q: 'static + LetterSpacingTheme>(
+        mut self,
+        key: &str,
+        alias: Q,
+    ) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.letter_spacings.push(AliasBinding::new(
+            key,
+            move |theme, value: String| {
+                theme.set_letter_spacing(alias.clone(), CssLetterSpacing::from(value.as_str()))
+            },
+            move |theme| {
+                theme
+                    .general_get::<Q, CssLetterSpacing>(get_alias.clone())
+                    .map(|v| css_var_declaration_value(&v))
+            },
+        ));
+        self
+    }
+
+    pub fn breakpoint<Q: 'static + BreakpointTheme>(mut self, key: &str, alias: Q) -> ThemeAliases {
+        let get_alias = alias.clone();
+        self.breakpoints.push(AliasBinding::new(
+            key,
+            move |theme, value: ThemeFileBreakpoint| {
+                theme.set_breakpoint(alias.clone(), (value.min, value.max))
+            },
+            move |theme| {
+                theme
+                    .get::<Q, (u32, Option<u32>)>(get_alias.clone())
+                    .map(|(min, max)| ThemeFileBreakpoint { min, max })
+            },
+        ));
+        self
+    }
+}
+
+fn set_aliases<V: Clone>(
+    mut theme: Theme,
+    bindings: &[AliasBinding<V>],
+    values: &BTreeMap<String, V>,
+    category: &str,
+    unmapped: &mut Vec<String>,
+) -> Theme {
+    for (key, value) in values {
+        if let Some(binding) = bindings.iter().find(|b| &b.key == key) {
+            theme = (binding.set)(theme, value.clone());
+        } else {
+            unmapped.push(format!("{}.{}", category, key));
+        }
+    }
+    theme
+}
+
+fn get_aliases<V>(theme: &Theme, bindings: &[AliasBinding<V>]) -> BTreeMap<String, V> {
+    bindings
+        .iter()
+        .filter_map(|b| (b.get)(theme).map(|value| (b.key.clone(), value)))
+        .collect()
+}
+
+fn scale_strings<V: std::fmt::Display>(scale: &[V]) -> Vec<String> {
+    scale.iter().map(css_var_declaration_value).collect()
+}
+
+fn scale_values<V: for<'a> From<&'a str>>(scale: &[String]) -> Vec<V> {
+    scale.iter().map(|v| V::from(v.as_str())).collect()
+}
+
+impl ThemeFile {
+    pub fn from_theme(theme: &Theme, aliases: &ThemeAliases) -> ThemeFile {
+        ThemeFile {
+            name: theme.name.clone(),
+            scales: ThemeFileScales {
+                spaces: scale_strings(&theme.spaces_scale),
+                font_sizes: scale_strings(&theme.font_sizes_scale),
+                fonts: scale_strings(&theme.fonts_scale),
+                font_weights: scale_strings(&theme.font_weights_scale),
+                line_heights: scale_strings(&theme.line_heights_scale),
+                letter_spacings: scale_strings(&theme.letter_spacings_scale),
+                sizes: scale_strings(&theme.sizes_scale),
+                borders: scale_strings(&theme.borders_scale),
+                border_styles: scale_strings(&theme.border_styles_scale),
+                border_widths: scale_strings(&theme.border_widths_scale),
+                breakpoints: theme.breakpoints_scale.clone(),
+                radii: scale_strings(&theme.radii_scale),
+                colors: scale_strings(&theme.colors_scale),
+                shadows: scale_strings(&theme.shadows_scale),
+            },
+            aliases: ThemeFileAliases {
+                colors: get_aliases(theme, &aliases.colors),
+                spaces: get_aliases(theme, &aliases.spaces),
+                sizes: get_aliases(theme, &aliases.sizes),
+                font_sizes: get_aliases(theme, &aliases.font_sizes),
+                shadows: get_aliases(theme, &aliases.shadows),
+                borders: get_aliases(theme, &aliases.borders),
+                border_widths: get_aliases(theme, &aliases.border_widths),
+                border_styles: get_aliases(theme, &aliases.border_styles),
+                radii: get_aliases(theme, &aliases.radii),
+                transitions: get_aliases(theme, &aliases.transitions),
+                line_heights: get_aliases(theme, &aliases.line_heights),
+                letter_spacings: get_aliases(theme, &aliases.letter_spacings),
+                breakpoints: get_aliases(theme, &aliases.breakpoints),
+            },
+        }
+    }
+
+    // Builds the theme, also returning the `category.key` of every alias with no registration.
+    pub fn to_theme_reporting_unmapped(&self, aliases: &ThemeAliases) -> (Theme, Vec<String>) {
+        let scales = &self.scales;
+        let mut theme = Theme::new(&self.name)
+            .space_scale(&scale_values::<CssSpace>(&scales.spaces))
+            .font_size_scale(&scale_values::<CssFontSize>(&scales.font_sizes))
+            .font_weight_scale(&scale_values::<CssFontWeight>(&scales.font_weights))
+            .line_height_scale(&scale_values::<CssLineHeight>(&scales.line_heights))
+            .letter_spacing_scale(&scale_values::<CssLetterSpacing>(&scales.letter_spacings))
+            .size_scale(&scale_values::<CssSize>(&scales.sizes))
+            .border_scale(&scale_values::<CssBorder>(&scales.borders))
+            .border_width_scale(&scale_values::<CssBorderWidth>(&scales.border_widths));
+
+        if !scales.breakpoints.is_empty() {
+            theme = theme.breakpoint_scale(scales.breakpoints.clone());
+        }
+        theme.fonts_scale = scale_values(&scales.fonts);
+        theme.border_styles_scale = scale_values(&scales.border_styles);
+        theme.radii_scale = scale_values(&scales.radii);
+        theme.colors_scale = scale_values(&scales.colors);
+        theme.shadows_scale = scale_values(&scales.shadows);
+
+        let file = &self.aliases;
+        let mut unmapped = vec![];
+        theme = set_aliases(
+            theme,
+            &aliases.colors,
+            &file.colors,
+            "colors",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.spaces,
+            &file.spaces,
+            "spaces",
+            &mut unmapped,
+        );
+        theme = set_aliases(theme, &aliases.sizes, &file.sizes, "sizes", &mut unmapped);
+        theme = set_aliases(
+            theme,
+            &aliases.font_sizes,
+            &file.font_sizes,
+            "font_sizes",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.shadows,
+            &file.shadows,
+            "shadows",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.borders,
+            &file.borders,
+            "borders",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.border_widths,
+            &file.border_widths,
+            "border_widths",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.border_styles,
+            &file.border_styles,
+            "border_styles",
+            &mut unmapped,
+        );
+        theme = set_aliases(theme, &aliases.radii, &file.radii, "radii", &mut unmapped);
+        theme = set_aliases(
+            theme,
+            &aliases.transitions,
+            &file.transitions,
+            "transitions",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.line_heights,
+            &file.line_heights,
+            "line_heights",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.letter_spacings,
+            &file.letter_spacings,
+            "letter_spacings",
+            &mut unmapped,
+        );
+        theme = set_aliases(
+            theme,
+            &aliases.breakpoints,
+            &file.breakpoints,
+            "breakpoints",
+            &mut unmapped,
+        );
+
+        (theme, unmapped)
+    }
+
+    // Aliases without a registration are skipped, use `to_theme_reporting_unmapped` to list them.
+    pub fn to_theme(&self, aliases: &ThemeAliases) -> Theme {
+        self.to_theme_reporting_unmapped(aliases).0
+    }
+}
+
+impl Theme {
+    pub fn from_json(src: &str, aliases: &ThemeAliases) -> Result<Theme, ThemeFileError> {
+        let file: ThemeFile = serde_json::from_str(src).map_err(ThemeFileError::Json)?;
+        Ok(file.to_theme(aliases))
+    }
+
+    pub fn from_toml(src: &str, aliases: &ThemeAliases) -> Result<Theme, ThemeFileError> {
+        let file: ThemeFile = toml::from_str(src).map_err(ThemeFileError::TomlDe)?;
+        Ok(file.to_theme(aliases))
+    }
+
+    pub fn to_json(&self, aliases: &ThemeAliases) -> Result<String, ThemeFileError> {
+        serde_json::to_string_pretty(&ThemeFile::from_theme(self, aliases))
+            .map_err(ThemeFileError::Json)
+    }
+
+    pub fn to_toml(&self, aliases: &ThemeAliases) -> Result<String, ThemeFileError> {
+        toml::to_string_pretty(&ThemeFile::from_theme(self, aliases))
+            .map_err(ThemeFileError::TomlSer)
+    }
+}
@@ -0,0 +1,282 @@
+use crate::style::css_values::*;
+use crate::style::theme::*;
+use crate::style::{log_warning, CssValueTrait, Style};
+use anymap::any::Any;
+use seed_hooks::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+// Theme completeness.
+//
+// A lookup of an alias that no loaded theme defines no longer panics. It logs a warning naming
+// the alias type, the alias and the value type, once per alias, and falls back to a default
+// that can be configured per value type with `set_theme_fallback`:
+//
+// set_theme_fallback(CssColor::from("black"));
+//
+// Unless configured, css values fall back to `unset`, styles to an empty style and
+// breakpoints to one that always matches.
+//
+// `Theme::validate::<AliasEnum>()` lists the aliases of a `#[derive(ThemeAlias)]` enum that a
+// theme does not define, and `debug_check_app_themes` logs every missing alias and empty scale
+// of the loaded app themes in debug builds:
+//
+// load_app_themes(&[default_colors_theme, brand_theme]);
+// debug_check_app_themes(&[validate_app_themes::<Brand>, validate_app_themes::<Spacing>]);
+
+thread_local! {
+    static THEME_FALLBACKS: RefCell<anymap::Map<dyn Any>> = RefCell::new(anymap::Map::new());
+    static ALIAS_NAMES: RefCell<anymap::Map<dyn Any>> = RefCell::new(anymap::Map::new());
+    static REPORTED_MISSING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingThemeValue {
+    pub alias_type: &'static str,
+    // the variant name, when the alias type is known through `ThemeAlias`
+    pub alias: Option<String>,
+    pub value_type: &'static str,
+}
+
+impl std::fmt::Display for MissingThemeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no theme defines a {} for {}::{}",
+            self.value_type,
+            self.alias_type,
+            self.alias.as_deref().unwrap_or("<unnamed alias>")
+        )
+    }
+}
+
+pub trait ThemeFallback: Clone + 'static {
+    fn default_fallback() -> Self;
+}
+
+impl<V> ThemeFallback for V
+where
+    V: CssValueTrait + for<'a> From<&'a str> + 'static,
+{
+    fn default_fallback() -> Self {
+        V::from("unset")
+    }
+}
+
+impl ThemeFallback for Style {
+    fn default_fallback() -> Self {
+        Style::default()
+    }
+}
+
+impl ThemeFallback for (u32, Option<u32>) {
+    fn default_fallback() -> Self {
+        (0, None)
+    }
+}
+
+pub fn set_theme_fallback<V: ThemeFallback>(value: V) {
+    THEME_FALLBACKS.with(|fallbacks| {
+        fallbacks.borrow_mut().insert(value);
+    })
+}
+
+pub fn theme_fallback<V: ThemeFallback>() -> V {
+    THEME_FALLBACKS
+        .with(|fallbacks| fallbacks.borrow().get::<V>().cloned())
+        .unwrap_or_else(V::default_fallback)
+}
+
+struct AliasName<Q>(fn(&Q) -> &'static str);
+
+// Makes the variant names of `Q` available to warnings about failed lookups.
+pub fn register_alias_names<Q: ThemeAlias>() {
+    ALIAS_NAMES.with(|names| {
+        names
+            .borrow_mut()
+            .insert(AliasName::<Q>(|alias| alias.alias_name()));
+    })
+}
+
+fn alias_name<Q: 'static>(alias: &Q) -> Option<String> {
+    ALIAS_NAMES.with(|names| {
+        names
+            .borrow()
+            .get::<AliasName<Q>>()
+            .map(|AliasName(name)| name(alias).to_string())
+    })
+}
+
+fn log_missing_once(message: String) {
+    let first_time =
+        REPORTED_MISSING.with(|reported| reported.borrow_mut().insert(message.clone()));
+    if first_time {
+        log_warning(format!("seed_style warning: {}", message));
+    }
+}
+
+// Used in place of a failed alias lookup.
+pub(crate) fn missing_theme_value<Q: 'static, V: ThemeFallback>(alias: &Q) -> V {
+    let missing = MissingThemeValue {
+        alias_type: std::any::type_name::<Q>(),
+        alias: alias_name(alias),
+        value_type: std::any::type_name::<V>(),
+    };
+    log_missing_once(format!("{}, using the fallback", missing));
+    theme_fallback::<V>()
+}
+
+// Used in place of a failed scale lookup such as `.padding(3)`.
+pub(crate) fn missing_theme_scale_value<V: ThemeFallback>(scale: &str, idx: usize) -> V {
+    log_missing_once(format!(
+        "no theme defines index {} of {}, using the fallback",
+        idx, scale
+    ));
+    theme_fallback::<V>()
+}
+
+pub(crate) fn missing_breakpoints() {
+    log_missing_once(
+        "no theme defines a breakpoint scale, responsive values use their first entry".to_string(),
+    );
+}
+
+fn has_alias_value<Q, V>(theme: &Theme, alias: &Q) -> bool
+where
+    Q: 'static + Eq + Hash,
+    V: 'static,
+{
+    theme
+        .anymap
+        .get::<HashMap<Q, V>>()
+        .map_or(false, |hm| hm.contains_key(alias))
+}
+
+// theme kind as used in `#[theme(..)]` -> whether the theme defines a value for the alias, and the value type.
+fn kind_check<Q: ThemeAlias>(kind: &str) -> Option<(fn(&Theme, &Q) -> bool, &'static str)> {
+    macro_rules! check {
+        ($value:ty) => {
+            Some((
+                has_alias_value::<Q, $value>,
+                std::any::type_name::<$value>(),
+            ))
+        };
+    }
+    match kind {
+        "color" => check!(CssColor),
+        "space" => check!(CssSpace),
+        "size" => check!(CssSize),
+        "font_size" => check!(CssFontSize),
+        "shadow" => check!(CssShadow),
+        "border" => check!(CssBorder),
+        "border_width" => check!(CssBorderWidth),
+        "border_style" => check!(CssBorderStyle),
+        "radius" | "border_radius" => check!(CssBorderRadius),
+        "transition" => check!(CssTransition),
+        "line_height" => check!(CssLineHeight),
+        "letter_spacing" => check!(CssLetterSpacing),
+        "z_index" => check!(CssZIndex),
+        "breakpoint" => check!((u32, Option<u32>)),
+        "style" => check!(Style),
+        _ => None,
+    }
+}
+
+fn missing_aliases<'a, Q, It>(themes: It) -> Vec<MissingThemeValue>
+where
+    Q: ThemeAlias,
+    It: Iterator<Item = &'a Theme> + Clone,
+{
+    let mut missing = vec![];
+    for kind in Q::theme_kinds() {
+        if let Some((is_defined, value_type)) = kind_check::<Q>(kind) {
+            for alias in Q::variants() {
+                if !themes.clone().any(|theme| is_defined(theme, &alias)) {
+                    missing.push(MissingThemeValue {
+                        alias_type: std::any::type_name::<Q>(),
+                        alias: Some(alias.alias_name().to_string()),
+                        value_type,
+                    });
+                }
+            }
+        }
+    }
+    missing
+}
+
+impl Theme {
+    // Every variant of `Q` that this theme has no value for.
+    pub fn validate<Q: ThemeAlias>(&self) -> Vec<MissingThemeValue> {
+        register_alias_names::<Q>();
+        missing_aliases::<Q, _>(std::iter::once(self))
+    }
+
+    pub fn empty_scales(&self) -> Vec<&'static str> {
+        let scales: [(&'static str, bool); 14] = [
+            ("spaces_scale", self.spaces_scale.is_empty()),
+            ("font_sizes_scale", self.font_sizes_scale.is_empty()),
+            ("fonts_scale", self.fonts_scale.is_empty()),
+            ("font_weights_scale", self.font_weights_scale.is_empty()),
+            ("line_heights_scale", self.line_heights_scale.is_empty()),
+            (
+                "letter_spacings_scale",
+                self.letter_spacings_scale.is_empty(),
+            ),
+            ("sizes_scale", self.sizes_scale.is_empty()),
+            ("borders_scale", self.borders_scale.is_empty()),
+            ("border_styles_scale", self.border_styles_scale.is_empty()),
+            ("border_widths_scale", self.border_widths_scale.is_empty()),
+            ("breakpoints_scale", self.breakpoints_scale.is_empty()),
+            ("radii_scale", self.radii_scale.is_empty()),
+            ("colors_scale", self.colors_scale.is_empty()),
+            ("shadows_scale", self.shadows_scale.is_empty()),
+        ];
+        scales
+            .iter()
+            .filter(|(_, empty)| *empty)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+// Variants of `Q` that none of the loaded app themes define.
+pub fn validate_app_themes<Q: ThemeAlias>() -> Vec<MissingThemeValue> {
+    register_alias_names::<Q>();
+    app_themes().observe_with(|themes| missing_aliases::<Q, _>(themes.iter()))
+}
+
+// Scales that none of the loaded app themes define.
+pub fn empty_app_theme_scales() -> Vec<&'static str> {
+    app_themes().observe_with(|themes| {
+        let mut empty: Option<Vec<&'static str>> = None;
+        for theme in themes {
+            let theme_empty = theme.empty_scales();
+            empty = Some(match empty {
+                Some(empty) => empty
+                    .into_iter()
+                    .filter(|s| theme_empty.contains(s))
+                    .collect(),
+                None => theme_empty,
+            });
+        }
+        empty.unwrap_or_default()
+    })
+}
+
+// Startup check, logs every missing alias and empty scale in debug builds and does nothing in release.
+pub fn debug_check_app_themes(checks: &[fn() -> Vec<MissingThemeValue>]) {
+    if cfg!(debug_assertions) {
+        for check in checks {
+            for missing in check() {
+                log_warning(format!("seed_style theme check: {}", missing));
+            }
+        }
+        for scale in empty_app_theme_scales() {
+            log_warning(format!(
+                "seed_style theme check: no theme defines {}",
+                scale
+            ));
+        }
+    }
+}